@@ -1,4 +1,4 @@
-use prediction_market_event_nostr_client::cli::parse_and_handle;
+use prediction_market_event_nostr_client::cli::{exit_code, parse_and_handle, success_exit_code};
 
 #[tokio::main]
 async fn main() {
@@ -6,10 +6,12 @@ async fn main() {
         Ok(v) => {
             let json_pretty =
                 serde_json::to_string_pretty(&v).expect("failed to serialize cli value");
-            println!("{json_pretty}")
+            println!("{json_pretty}");
+            std::process::exit(success_exit_code(&v))
         }
         Err(e) => {
-            println!("ERROR: {e}")
+            println!("ERROR: {e:#}");
+            std::process::exit(exit_code(&e))
         }
     }
 }
@@ -1,15 +1,32 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Error, Result};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use home::home_dir;
-use nostr_sdk::{Keys, Url};
-use sqlx::sqlite::SqliteConnectOptions;
+use nostr_sdk::bip39::Mnemonic;
+use nostr_sdk::nips::nip06::FromMnemonic;
+use nostr_sdk::nips::nip46::NostrConnectURI;
+use nostr_sdk::nips::nip49::EncryptedSecretKey;
+use nostr_sdk::nips::nip65::RelayMetadata;
+use nostr_sdk::signer::Nip46Signer;
+use nostr_sdk::util::hex;
+use nostr_sdk::{
+    Alphabet, Event, EventId, Filter, FromBech32, JsonUtil, Keys, NostrSigner, PublicKey,
+    SingleLetterTag, Timestamp, ToBech32, Url,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteRow};
 use sqlx::Row;
 use sqlx::{Pool, Sqlite, SqlitePool};
 
-use super::Context;
+use super::{stdin_prompts, Context};
 
 pub mod table_text_json;
 
@@ -38,54 +55,622 @@ pub async fn get_db() -> Result<Pool<Sqlite>> {
 
 pub async fn initialize_db(db_pool: &Pool<Sqlite>) -> Result<()> {
     NostrSecretKey::init_table(db_pool).await?;
+    NostrSecretKey::migrate_unnamed_key(db_pool).await?;
+    Settings::init_table(db_pool).await?;
+    NostrMnemonic::init_table(db_pool).await?;
     NostrRelays::init_table(db_pool).await?;
+    NostrRelays::migrate_relays_without_info(db_pool).await?;
+    NostrEventCache::init_table(db_pool).await?;
+    Outbox::init_table(db_pool).await?;
 
     Ok(())
 }
 
+pub struct Settings;
+table_text_json::impl_table!(Settings, "settings", serde_json::Value);
+
+impl Settings {
+    const ACTIVE_IDENTITY: &'static str = "active_identity";
+    const NOSTR_CONNECT_APP_SECRET_KEY: &'static str = "nostr_connect_app_secret_key";
+    const MIN_RELAY_ACKS: &'static str = "min_relay_acks";
+
+    pub async fn get_active_identity(context: &Context) -> Result<Option<String>> {
+        let Some(value) = Self::get(context, Self::ACTIVE_IDENTITY).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(serde_json::from_value(value)?))
+    }
+
+    pub async fn set_active_identity(context: &Context, name: &str) -> Result<()> {
+        Self::put(context, Self::ACTIVE_IDENTITY, &serde_json::json!(name)).await?;
+
+        Ok(())
+    }
+
+    /// Number of relays that have to accept an event for a publish to count as successful, 1 if not set.
+    pub async fn get_min_relay_acks(context: &Context) -> Result<usize> {
+        let Some(value) = Self::get(context, Self::MIN_RELAY_ACKS).await? else {
+            return Ok(1);
+        };
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// At least one relay has to accept an event, otherwise an event no relay accepted would not be queued.
+    pub async fn set_min_relay_acks(context: &Context, min_relay_acks: NonZeroUsize) -> Result<()> {
+        Self::put(
+            context,
+            Self::MIN_RELAY_ACKS,
+            &serde_json::json!(min_relay_acks),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Keys this cli uses to talk to NIP-46 remote signers, created on first use.
+    pub async fn get_nostr_connect_app_keys(context: &Context) -> Result<Keys> {
+        if let Some(value) = Self::get(context, Self::NOSTR_CONNECT_APP_SECRET_KEY).await? {
+            let secret_key: String = serde_json::from_value(value)?;
+            return Ok(Keys::parse(secret_key)?);
+        }
+
+        let keys = Keys::generate();
+        Self::put(
+            context,
+            Self::NOSTR_CONNECT_APP_SECRET_KEY,
+            &serde_json::json!(keys.secret_key().to_secret_hex()),
+        )
+        .await?;
+
+        Ok(keys)
+    }
+}
+
+/// Secret keys, or bunker URIs of NIP-46 remote signers, by identity name.
 pub struct NostrSecretKey;
 table_text_json::impl_table!(NostrSecretKey, "nostr_secret_key", String);
 
 impl NostrSecretKey {
+    pub const DEFAULT_IDENTITY: &'static str = "default";
+    const NOSTR_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Moves the key stored before named identities existed to the default identity.
+    async fn migrate_unnamed_key(db_pool: &Pool<Sqlite>) -> Result<()> {
+        let raw = format!(
+            "
+                UPDATE OR IGNORE {}
+                SET k = ?
+                WHERE k = ''
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        sqlx::query(&raw)
+            .bind(Self::DEFAULT_IDENTITY)
+            .execute(db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Identity from the `--identity` flag, otherwise the one set with `key use`, otherwise the default one.
+    pub async fn active_identity(context: &Context) -> Result<String> {
+        if let Some(identity) = &context.identity {
+            return Ok(identity.to_owned());
+        }
+        if let Some(identity) = Settings::get_active_identity(context).await? {
+            return Ok(identity);
+        }
+
+        Ok(Self::DEFAULT_IDENTITY.to_owned())
+    }
+
+    /// Connects to the remote signer if the identity uses one.
+    pub async fn get_signer(context: &Context) -> Result<NostrSigner> {
+        let secret_key = Self::get_active_secret_key(context).await?;
+        if Self::is_nostr_connect_value(&secret_key) {
+            let uri = NostrConnectURI::parse(secret_key)?;
+            return Self::nostr_connect_signer(context, uri).await;
+        }
+
+        Ok(Self::get_keys(context).await?.into())
+    }
+
+    /// Public key without connecting to the remote signer if the identity uses one.
+    pub async fn get_public_key(context: &Context) -> Result<PublicKey> {
+        let secret_key = Self::get_active_secret_key(context).await?;
+        if Self::is_nostr_connect_value(&secret_key) {
+            return Self::nostr_connect_public_key(&secret_key);
+        }
+
+        Ok(Self::get_keys(context).await?.public_key)
+    }
+
+    /// Uses the NIP-46 remote signer at the bunker URI for the active identity.
+    /// Fails if the identity already has a local key.
+    pub async fn connect(context: &Context, uri: NostrConnectURI) -> Result<PublicKey> {
+        if !uri.is_bunker() {
+            bail!("expected bunker:// URI")
+        }
+        let identity = Self::active_identity(context).await?;
+        if let Some(secret_key) = Self::get(context, &identity).await? {
+            if !Self::is_nostr_connect_value(&secret_key) {
+                bail!("identity {identity} already has a local key")
+            }
+        }
+
+        let public_key = Self::nostr_connect_signer(context, uri.clone())
+            .await?
+            .public_key()
+            .await?;
+        Self::put(context, identity, &uri.to_string()).await?;
+
+        Ok(public_key)
+    }
+
+    /// Prompts for the passphrase if the stored key is encrypted.
     pub async fn get_keys(context: &Context) -> Result<Keys> {
-        let mut secret_key_option = Self::get(context, "").await?;
-        if let None = secret_key_option {
-            let keys = Keys::generate();
-            let secret_key = keys.secret_key().to_secret_hex();
-            Self::put(context, "", &secret_key).await?;
-            secret_key_option = Some(secret_key);
+        let secret_key = Self::get_active_secret_key(context).await?;
+
+        if Self::is_nostr_connect_value(&secret_key) {
+            bail!("key is held by a remote signer")
+        }
+        if Self::is_encrypted_value(&secret_key) {
+            let passphrase = stdin_prompts::passphrase_prompt()?;
+            return Self::decrypt_value(&secret_key, &passphrase);
         }
-        let keys = Keys::parse(secret_key_option.unwrap())?;
+        let keys = Keys::parse(secret_key)?;
 
         Ok(keys)
     }
 
+    /// Also forgets the mnemonic the previous key was derived from.
+    /// A key replacing an encrypted key is encrypted with the same passphrase.
     pub async fn set_keys(context: &Context, keys: Option<&Keys>) -> Result<()> {
+        let identity = Self::active_identity(context).await?;
         match keys {
             Some(keys) => {
-                let secret_key_hex = keys.secret_key().to_secret_hex();
-                Self::put(context, "", &secret_key_hex).await?;
+                Self::replace_keys(context, &identity, keys).await?;
             }
             None => {
-                Self::delete(context, "").await?;
+                Self::delete(context, &identity).await?;
+            }
+        }
+        NostrMnemonic::delete(context, identity).await?;
+
+        Ok(())
+    }
+
+    /// Returns the passphrase the key was encrypted with, if the replaced key was encrypted.
+    async fn replace_keys(
+        context: &Context,
+        identity: &str,
+        keys: &Keys,
+    ) -> Result<Option<String>> {
+        let passphrase = match Self::get(context, identity).await? {
+            Some(secret_key) if Self::is_encrypted_value(&secret_key) => {
+                let passphrase = stdin_prompts::passphrase_prompt()?;
+                // A mistyped passphrase must not become the passphrase of the new key.
+                Self::decrypt_value(&secret_key, &passphrase)?;
+                Some(passphrase)
             }
+            _ => None,
+        };
+        let value = match &passphrase {
+            Some(passphrase) => Self::encrypt_value(keys, passphrase)?,
+            None => keys.secret_key().to_secret_hex(),
+        };
+        Self::put(context, identity, &value).await?;
+
+        Ok(passphrase)
+    }
+
+    /// Generates and stores a new key for the active identity, fails if it already has one.
+    pub async fn generate_keys(context: &Context) -> Result<Keys> {
+        let identity = Self::active_identity(context).await?;
+        let keys = Keys::generate();
+        Self::add_identity(context, &identity, &keys).await?;
+
+        Ok(keys)
+    }
+
+    /// Same as [NostrSecretKey::generate_keys] but the key is derived from a new 24 word mnemonic (NIP-06).
+    pub async fn generate_mnemonic_keys(context: &Context) -> Result<(Keys, MnemonicBackup)> {
+        let identity = Self::active_identity(context).await?;
+        if Self::get(context, &identity).await?.is_some() {
+            bail!("identity {identity} already exists")
+        }
+
+        let entropy: [u8; 32] = nostr_sdk::secp256k1::rand::random();
+        let mnemonic = Mnemonic::from_entropy(&entropy)?;
+        let keys = Self::import_mnemonic(context, &mnemonic.to_string(), None, 0, false).await?;
+        let mnemonic_backup = MnemonicBackup {
+            mnemonic: mnemonic.to_string(),
+            account: 0,
+            passphrase: false,
+        };
+
+        Ok((keys, mnemonic_backup))
+    }
+
+    /// Derives the key from a BIP-39 mnemonic along the NIP-06 path and stores it for the active identity.
+    /// An existing key is only replaced with `force`, an encrypted one stays encrypted together with the mnemonic.
+    pub async fn import_mnemonic(
+        context: &Context,
+        mnemonic: &str,
+        passphrase: Option<&str>,
+        account: u32,
+        force: bool,
+    ) -> Result<Keys> {
+        let identity = Self::active_identity(context).await?;
+        if !force && Self::get(context, &identity).await?.is_some() {
+            bail!("identity {identity} already has a key, replace it with --force")
         }
 
+        let mnemonic = Mnemonic::parse_normalized(mnemonic)?;
+        let keys = Keys::from_mnemonic_with_account(
+            mnemonic.to_string().as_str(),
+            passphrase,
+            Some(account),
+        )?;
+        let key_passphrase = Self::replace_keys(context, &identity, &keys).await?;
+
+        let mut mnemonic_backup = MnemonicBackup {
+            mnemonic: mnemonic.to_string(),
+            account,
+            passphrase: passphrase.is_some_and(|passphrase| !passphrase.is_empty()),
+        };
+        if let Some(key_passphrase) = key_passphrase {
+            mnemonic_backup = NostrMnemonic::encrypt(mnemonic_backup, &key_passphrase)?;
+        }
+        NostrMnemonic::put(context, identity, &mnemonic_backup).await?;
+
+        Ok(keys)
+    }
+
+    /// Prompts for the passphrase if the stored mnemonic is encrypted.
+    pub async fn export_mnemonic(context: &Context) -> Result<MnemonicBackup> {
+        let identity = Self::active_identity(context).await?;
+        let Some(mnemonic_backup) = NostrMnemonic::get(context, &identity).await? else {
+            bail!("key of identity {identity} was not derived from a stored mnemonic")
+        };
+        if NostrMnemonic::is_encrypted(&mnemonic_backup) {
+            let passphrase = stdin_prompts::passphrase_prompt()?;
+            return NostrMnemonic::decrypt(mnemonic_backup, &passphrase);
+        }
+
+        Ok(mnemonic_backup)
+    }
+
+    pub async fn add_identity(context: &Context, name: &str, keys: &Keys) -> Result<()> {
+        if Self::get(context, name).await?.is_some() {
+            bail!("identity {name} already exists")
+        }
+
+        let secret_key_hex = keys.secret_key().to_secret_hex();
+        Self::put(context, name, &secret_key_hex).await?;
+
         Ok(())
     }
+
+    pub async fn use_identity(context: &Context, name: &str) -> Result<()> {
+        if Self::get(context, name).await?.is_none() {
+            bail!("identity {name} does not exist")
+        }
+
+        Settings::set_active_identity(context, name).await?;
+
+        Ok(())
+    }
+
+    pub async fn list_identities(context: &Context) -> Result<Vec<Identity>> {
+        let mnemonic_identities: Vec<String> = NostrMnemonic::get_all(context)
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut identities = Vec::new();
+        for (name, secret_key) in Self::get_all(context).await? {
+            let encrypted = Self::is_encrypted_value(&secret_key);
+            let remote = Self::is_nostr_connect_value(&secret_key);
+            let public_key = match (encrypted, remote) {
+                (true, _) => None,
+                (false, true) => Some(Self::nostr_connect_public_key(&secret_key)?),
+                (false, false) => Some(Keys::parse(secret_key)?.public_key),
+            };
+            let mnemonic = mnemonic_identities.contains(&name);
+            identities.push(Identity {
+                name,
+                public_key,
+                encrypted,
+                remote,
+                mnemonic,
+            });
+        }
+
+        Ok(identities)
+    }
+
+    /// Encrypts the stored key as NIP-49 ncryptsec and the stored mnemonic with the same passphrase.
+    pub async fn encrypt_keys(context: &Context, passphrase: &str) -> Result<()> {
+        let secret_key = Self::get_active_secret_key(context).await?;
+        if Self::is_nostr_connect_value(&secret_key) {
+            bail!("key is held by a remote signer")
+        }
+        if Self::is_encrypted_value(&secret_key) {
+            bail!("key is already encrypted")
+        }
+
+        let keys = Keys::parse(secret_key)?;
+        let identity = Self::active_identity(context).await?;
+        Self::put(context, &identity, &Self::encrypt_value(&keys, passphrase)?).await?;
+        if let Some(mnemonic_backup) = NostrMnemonic::get(context, &identity).await? {
+            let mnemonic_backup = NostrMnemonic::encrypt(mnemonic_backup, passphrase)?;
+            NostrMnemonic::put(context, identity, &mnemonic_backup).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores the key unencrypted.
+    pub async fn decrypt_keys(context: &Context, passphrase: &str) -> Result<()> {
+        let secret_key = Self::get_active_secret_key(context).await?;
+        if !Self::is_encrypted_value(&secret_key) {
+            bail!("key is not encrypted")
+        }
+
+        let keys = Self::decrypt_value(&secret_key, passphrase)?;
+        let identity = Self::active_identity(context).await?;
+        Self::put(context, &identity, &keys.secret_key().to_secret_hex()).await?;
+        if let Some(mnemonic_backup) = NostrMnemonic::get(context, &identity).await? {
+            let mnemonic_backup = NostrMnemonic::decrypt(mnemonic_backup, passphrase)?;
+            NostrMnemonic::put(context, identity, &mnemonic_backup).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn change_passphrase(
+        context: &Context,
+        passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        let secret_key = Self::get_active_secret_key(context).await?;
+        if !Self::is_encrypted_value(&secret_key) {
+            bail!("key is not encrypted")
+        }
+
+        let keys = Self::decrypt_value(&secret_key, passphrase)?;
+        let identity = Self::active_identity(context).await?;
+        Self::put(
+            context,
+            &identity,
+            &Self::encrypt_value(&keys, new_passphrase)?,
+        )
+        .await?;
+        if let Some(mnemonic_backup) = NostrMnemonic::get(context, &identity).await? {
+            let mnemonic_backup = NostrMnemonic::decrypt(mnemonic_backup, passphrase)?;
+            let mnemonic_backup = NostrMnemonic::encrypt(mnemonic_backup, new_passphrase)?;
+            NostrMnemonic::put(context, identity, &mnemonic_backup).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_active_secret_key(context: &Context) -> Result<String> {
+        let identity = Self::active_identity(context).await?;
+        let Some(secret_key) = Self::get(context, &identity).await? else {
+            bail!("no key for identity {identity}, create one with `key generate`")
+        };
+
+        Ok(secret_key)
+    }
+
+    fn is_encrypted_value(secret_key: &str) -> bool {
+        secret_key.starts_with("ncryptsec")
+    }
+
+    fn is_nostr_connect_value(secret_key: &str) -> bool {
+        secret_key.starts_with("bunker://")
+    }
+
+    fn nostr_connect_public_key(uri: &str) -> Result<PublicKey> {
+        let Some(public_key) = NostrConnectURI::parse(uri)?.signer_public_key() else {
+            bail!("bunker URI without signer public key")
+        };
+
+        Ok(public_key)
+    }
+
+    async fn nostr_connect_signer(context: &Context, uri: NostrConnectURI) -> Result<NostrSigner> {
+        let app_keys = Settings::get_nostr_connect_app_keys(context).await?;
+        let signer = Nip46Signer::new(uri, app_keys, Self::NOSTR_CONNECT_TIMEOUT, None).await?;
+
+        Ok(NostrSigner::nip46(signer))
+    }
+
+    fn encrypt_value(keys: &Keys, passphrase: &str) -> Result<String> {
+        let ncryptsec = keys.secret_key().encrypt(passphrase)?.to_bech32()?;
+
+        Ok(ncryptsec)
+    }
+
+    fn decrypt_value(ncryptsec: &str, passphrase: &str) -> Result<Keys> {
+        let secret_key = EncryptedSecretKey::from_bech32(ncryptsec)?
+            .to_secret_key(passphrase)
+            .map_err(|_| Error::msg("failed to decrypt key, wrong passphrase?"))?;
+
+        Ok(Keys::new(secret_key))
+    }
+}
+
+pub struct Identity {
+    pub name: String,
+    /// [None] if the key is encrypted.
+    pub public_key: Option<PublicKey>,
+    pub encrypted: bool,
+    /// Key is held by a NIP-46 remote signer.
+    pub remote: bool,
+    /// Key was derived from a stored mnemonic.
+    pub mnemonic: bool,
+}
+
+/// Mnemonics that identity keys were derived from, by identity name.
+pub struct NostrMnemonic;
+table_text_json::impl_table!(NostrMnemonic, "nostr_mnemonic", MnemonicBackup);
+
+#[derive(Serialize, Deserialize)]
+pub struct MnemonicBackup {
+    /// Encrypted with the passphrase of the key if the key is encrypted.
+    pub mnemonic: String,
+    pub account: u32,
+    /// Whether a BIP-39 passphrase is needed to derive the key, the passphrase itself is not stored.
+    pub passphrase: bool,
+}
+
+impl NostrMnemonic {
+    /// NIP-49 only fits secret keys, so mnemonics are encrypted with the same scrypt and XChaCha20-Poly1305 parameters.
+    const ENCRYPTED_PREFIX: &'static str = "ncryptmnemonic:";
+    const SCRYPT_LOG_N: u8 = 16;
+    const SALT_SIZE: usize = 16;
+    const NONCE_SIZE: usize = 24;
+
+    fn is_encrypted(mnemonic_backup: &MnemonicBackup) -> bool {
+        mnemonic_backup.mnemonic.starts_with(Self::ENCRYPTED_PREFIX)
+    }
+
+    fn encrypt(mnemonic_backup: MnemonicBackup, passphrase: &str) -> Result<MnemonicBackup> {
+        let salt: [u8; Self::SALT_SIZE] = nostr_sdk::secp256k1::rand::random();
+        let nonce: [u8; Self::NONCE_SIZE] = nostr_sdk::secp256k1::rand::random();
+        let ciphertext = Self::cipher(passphrase, &salt)?
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                mnemonic_backup.mnemonic.as_bytes(),
+            )
+            .map_err(|_| Error::msg("failed to encrypt mnemonic"))?;
+
+        Ok(MnemonicBackup {
+            mnemonic: format!(
+                "{}{}",
+                Self::ENCRYPTED_PREFIX,
+                hex::encode([salt.as_slice(), &nonce, &ciphertext].concat())
+            ),
+            ..mnemonic_backup
+        })
+    }
+
+    fn decrypt(mnemonic_backup: MnemonicBackup, passphrase: &str) -> Result<MnemonicBackup> {
+        let Some(encrypted) = mnemonic_backup
+            .mnemonic
+            .strip_prefix(Self::ENCRYPTED_PREFIX)
+        else {
+            bail!("mnemonic is not encrypted")
+        };
+        let encrypted = hex::decode(encrypted)?;
+        if encrypted.len() < Self::SALT_SIZE + Self::NONCE_SIZE {
+            bail!("encrypted mnemonic is too short")
+        }
+        let (salt, encrypted) = encrypted.split_at(Self::SALT_SIZE);
+        let (nonce, ciphertext) = encrypted.split_at(Self::NONCE_SIZE);
+        let mnemonic = Self::cipher(passphrase, salt)?
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::msg("failed to decrypt mnemonic, wrong passphrase?"))?;
+
+        Ok(MnemonicBackup {
+            mnemonic: String::from_utf8(mnemonic)?,
+            ..mnemonic_backup
+        })
+    }
+
+    fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305> {
+        let params = scrypt::Params::new(Self::SCRYPT_LOG_N, 8, 1, 32)?;
+        let mut key = [0u8; 32];
+        scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)?;
+
+        Ok(XChaCha20Poly1305::new(&key.into()))
+    }
 }
 
 pub struct NostrRelays;
-table_text_json::impl_table!(NostrRelays, "nostr_relays", ());
+table_text_json::impl_table!(NostrRelays, "nostr_relays", RelayInfo);
+
+#[derive(Serialize, Deserialize)]
+pub struct RelayInfo {
+    pub read: bool,
+    pub write: bool,
+    /// Disabled relays are kept but not used.
+    pub enabled: bool,
+    /// Unix seconds.
+    pub added_at: u64,
+    pub notes: Option<String>,
+}
+
+impl RelayInfo {
+    pub fn new(relay_metadata: Option<RelayMetadata>, notes: Option<String>) -> Self {
+        let mut relay_info = Self {
+            read: true,
+            write: true,
+            enabled: true,
+            added_at: Timestamp::now().as_u64(),
+            notes,
+        };
+        relay_info.set_relay_metadata(relay_metadata);
+
+        relay_info
+    }
+
+    pub fn set_relay_metadata(&mut self, relay_metadata: Option<RelayMetadata>) {
+        self.read = relay_metadata != Some(RelayMetadata::Write);
+        self.write = relay_metadata != Some(RelayMetadata::Read);
+    }
+
+    /// Role as in a NIP-65 relay list, [None] means both read and write.
+    pub fn relay_metadata(&self) -> Option<RelayMetadata> {
+        match (self.read, self.write) {
+            (true, false) => Some(RelayMetadata::Read),
+            (false, true) => Some(RelayMetadata::Write),
+            _ => None,
+        }
+    }
+}
 
 impl NostrRelays {
-    pub async fn add_url(context: &Context, url: Url) -> Result<()> {
-        let key = url.to_string();
-        Self::put(context, key, &()).await?;
+    /// Relays were stored without metadata before, they are used for both reading and writing.
+    async fn migrate_relays_without_info(db_pool: &Pool<Sqlite>) -> Result<()> {
+        let raw = format!(
+            "
+                UPDATE {}
+                SET v = json_object(
+                    'read', json('true'),
+                    'write', json('true'),
+                    'enabled', json('true'),
+                    'added_at', CAST(strftime('%s', 'now') AS INTEGER),
+                    'notes', NULL
+                )
+                WHERE v = 'null'
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        sqlx::query(&raw).execute(db_pool).await?;
 
         Ok(())
     }
 
+    /// Does nothing if the relay was already added.
+    pub async fn add_url(context: &Context, url: Url, relay_info: RelayInfo) -> Result<bool> {
+        let key = url.to_string();
+        if Self::get(context, &key).await?.is_some() {
+            return Ok(false);
+        }
+        Self::put(context, key, &relay_info).await?;
+
+        Ok(true)
+    }
+
     pub async fn remove_url(context: &Context, url: Url) -> Result<()> {
         let key = url.to_string();
         Self::delete(context, key).await?;
@@ -99,13 +684,392 @@ impl NostrRelays {
         Ok(())
     }
 
-    pub async fn get_all_urls(context: &Context) -> Result<Vec<Url>> {
+    pub async fn get_all_relays(context: &Context) -> Result<Vec<(Url, RelayInfo)>> {
         let mut h = Vec::new();
-        for (url_string, _) in Self::get_all(context).await?.into_iter() {
+        for (url_string, relay_info) in Self::get_all(context).await?.into_iter() {
             let url = Url::from_str(&url_string)?;
-            h.push(url);
+            h.push((url, relay_info));
         }
 
         Ok(h)
     }
+
+    /// Enabled relays with their role, ready for [crate::Client] constructors.
+    pub async fn get_enabled_relays(
+        context: &Context,
+    ) -> Result<Vec<(Url, Option<RelayMetadata>)>> {
+        let relays = Self::get_all_relays(context)
+            .await?
+            .into_iter()
+            .filter(|(_, relay_info)| relay_info.enabled && (relay_info.read || relay_info.write))
+            .map(|(url, relay_info)| (url, relay_info.relay_metadata()))
+            .collect();
+
+        Ok(relays)
+    }
+
+    pub async fn set_role(
+        context: &Context,
+        url: Url,
+        relay_metadata: Option<RelayMetadata>,
+    ) -> Result<()> {
+        Self::update(context, url, |relay_info| {
+            relay_info.set_relay_metadata(relay_metadata)
+        })
+        .await
+    }
+
+    pub async fn set_enabled(context: &Context, url: Url, enabled: bool) -> Result<()> {
+        Self::update(context, url, |relay_info| relay_info.enabled = enabled).await
+    }
+
+    async fn update(context: &Context, url: Url, f: impl FnOnce(&mut RelayInfo)) -> Result<()> {
+        let key = url.to_string();
+        let Some(mut relay_info) = Self::get(context, &key).await? else {
+            bail!("relay {url} was not added")
+        };
+        f(&mut relay_info);
+        Self::put(context, key, &relay_info).await?;
+
+        Ok(())
+    }
+}
+
+/// Raw signed nostr events fetched from relays.
+/// Every hashtag of an event is indexed, the hashtag filters relays use match any of them.
+pub struct NostrEventCache;
+
+impl NostrEventCache {
+    const SQL_TABLE_NAME: &'static str = "nostr_event_cache";
+    /// Every hashtag of a cached event, events can have more than one.
+    const HASHTAGS_SQL_TABLE_NAME: &'static str = "nostr_event_cache_hashtags";
+
+    async fn init_table(db_pool: &Pool<Sqlite>) -> Result<()> {
+        let raw = format!(
+            "
+                CREATE TABLE IF NOT EXISTS {0} (
+                    id TEXT PRIMARY KEY,
+                    kind INTEGER NOT NULL,
+                    author TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    json TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS {0}_kind ON {0} (kind);
+                CREATE INDEX IF NOT EXISTS {0}_author ON {0} (author);
+                CREATE INDEX IF NOT EXISTS {0}_created_at ON {0} (created_at);
+                CREATE TABLE IF NOT EXISTS {1} (
+                    event_id TEXT NOT NULL,
+                    hashtag TEXT NOT NULL,
+                    PRIMARY KEY (event_id, hashtag)
+                );
+                CREATE INDEX IF NOT EXISTS {1}_hashtag ON {1} (hashtag);
+            ",
+            Self::SQL_TABLE_NAME,
+            Self::HASHTAGS_SQL_TABLE_NAME
+        );
+
+        sqlx::raw_sql(&raw).execute(db_pool).await?;
+
+        Ok(())
+    }
+
+    pub async fn put_events(context: &Context, events: &[Event]) -> Result<()> {
+        let raw = format!(
+            "
+                INSERT INTO {} (id, kind, author, created_at, json)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO NOTHING
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        for event in events {
+            sqlx::query(&raw)
+                .bind::<String>(event.id.to_hex())
+                .bind::<i64>(event.kind.as_u16().into())
+                .bind::<String>(event.pubkey.to_hex())
+                .bind::<i64>(event.created_at.as_u64().try_into()?)
+                .bind::<String>(event.as_json())
+                .execute(&context.db_pool)
+                .await?;
+            Self::put_hashtags(&context.db_pool, event).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn put_hashtags(db_pool: &Pool<Sqlite>, event: &Event) -> Result<()> {
+        let raw = format!(
+            "
+                INSERT OR IGNORE INTO {} (event_id, hashtag)
+                VALUES (?, ?)
+            ",
+            Self::HASHTAGS_SQL_TABLE_NAME
+        );
+
+        for hashtag in event.hashtags() {
+            sqlx::query(&raw)
+                .bind::<String>(event.id.to_hex())
+                .bind::<String>(hashtag.to_owned())
+                .execute(db_pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the cached events matching any of the filters, newest first.
+    pub async fn get_events(context: &Context, filters: &[Filter]) -> Result<Vec<Event>> {
+        let mut events: Vec<Event> = Vec::new();
+        for filter in filters {
+            let mut conditions = Vec::new();
+            let mut binds = Vec::new();
+            if let Some(ids) = &filter.ids {
+                conditions.push(format!("id IN ({})", placeholders(ids.len())));
+                binds.extend(ids.iter().map(EventId::to_hex));
+            }
+            if let Some(kinds) = &filter.kinds {
+                let kinds: Vec<_> = kinds.iter().map(|k| k.as_u16().to_string()).collect();
+                conditions.push(format!("kind IN ({})", kinds.join(", ")));
+            }
+            if let Some(authors) = &filter.authors {
+                conditions.push(format!("author IN ({})", placeholders(authors.len())));
+                binds.extend(authors.iter().map(|author| author.to_hex()));
+            }
+            if let Some(hashtags) = filter
+                .generic_tags
+                .get(&SingleLetterTag::lowercase(Alphabet::T))
+            {
+                conditions.push(format!(
+                    "id IN (SELECT event_id FROM {} WHERE hashtag IN ({}))",
+                    Self::HASHTAGS_SQL_TABLE_NAME,
+                    placeholders(hashtags.len())
+                ));
+                binds.extend(hashtags.iter().cloned());
+            }
+            if let Some(since) = filter.since {
+                conditions.push(format!("created_at >= {}", since.as_u64()));
+            }
+            if let Some(until) = filter.until {
+                conditions.push(format!("created_at <= {}", until.as_u64()));
+            }
+            if conditions.is_empty() {
+                conditions.push("1".to_owned());
+            }
+
+            let raw = format!(
+                "
+                    SELECT json
+                    FROM {}
+                    WHERE {}
+                    ORDER BY created_at DESC
+                ",
+                Self::SQL_TABLE_NAME,
+                conditions.join(" AND ")
+            );
+
+            let mut query = sqlx::query(&raw);
+            for bind in binds {
+                query = query.bind::<String>(bind);
+            }
+            let rows = query.fetch_all(&context.db_pool).await?;
+
+            let mut matched = 0;
+            for row in rows {
+                if filter.limit.is_some_and(|limit| matched >= limit) {
+                    break;
+                }
+                let event = Event::from_json(row.get::<String, _>(0))?;
+                if !filter.match_event(&event) {
+                    continue;
+                }
+                matched += 1;
+                if !events.iter().any(|e| e.id == event.id) {
+                    events.push(event);
+                }
+            }
+        }
+        events.sort_by_key(|event| Reverse(event.created_at));
+
+        Ok(events)
+    }
+}
+
+/// Signed nostr events that were accepted by fewer relays than required, waiting to be sent again.
+pub struct Outbox;
+
+pub struct OutboxEntry {
+    pub event: Event,
+    /// Unix seconds.
+    pub queued_at: u64,
+    pub attempts: u32,
+    /// Unix seconds, [Outbox::get_due_entries] skips the entry until then.
+    pub next_attempt_at: u64,
+    /// Relays that accepted the event in any attempt.
+    pub acked_relays: HashSet<Url>,
+    pub last_error: Option<String>,
+}
+
+impl Outbox {
+    const SQL_TABLE_NAME: &'static str = "outbox";
+    const BACKOFF_BASE_SECONDS: u64 = 60;
+    const BACKOFF_MAX_SECONDS: u64 = 24 * 60 * 60;
+
+    async fn init_table(db_pool: &Pool<Sqlite>) -> Result<()> {
+        let raw = format!(
+            "
+                CREATE TABLE IF NOT EXISTS {0} (
+                    id TEXT PRIMARY KEY,
+                    json TEXT NOT NULL,
+                    queued_at INTEGER NOT NULL,
+                    attempts INTEGER NOT NULL,
+                    next_attempt_at INTEGER NOT NULL,
+                    acked_relays TEXT NOT NULL,
+                    last_error TEXT
+                );
+                CREATE INDEX IF NOT EXISTS {0}_next_attempt_at ON {0} (next_attempt_at);
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        sqlx::raw_sql(&raw).execute(db_pool).await?;
+
+        Ok(())
+    }
+
+    /// Records a failed or partial send of the event, adding it to the outbox if it is not there yet.
+    /// Attempts are spaced out with exponential backoff.
+    pub async fn record_attempt(
+        context: &Context,
+        event: &Event,
+        acked_relays: &HashSet<Url>,
+        error: Option<String>,
+    ) -> Result<OutboxEntry> {
+        let now = Timestamp::now().as_u64();
+        let mut entry = match Self::get_entry(context, &event.id).await? {
+            Some(entry) => entry,
+            None => OutboxEntry {
+                event: event.to_owned(),
+                queued_at: now,
+                attempts: 0,
+                next_attempt_at: now,
+                acked_relays: HashSet::new(),
+                last_error: None,
+            },
+        };
+        entry.attempts += 1;
+        entry.next_attempt_at = now + Self::backoff_seconds(entry.attempts);
+        entry.acked_relays.extend(acked_relays.iter().cloned());
+        entry.last_error = error;
+
+        let raw = format!(
+            "
+                INSERT INTO {} (id, json, queued_at, attempts, next_attempt_at, acked_relays, last_error)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    attempts = excluded.attempts,
+                    next_attempt_at = excluded.next_attempt_at,
+                    acked_relays = excluded.acked_relays,
+                    last_error = excluded.last_error
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        sqlx::query(&raw)
+            .bind::<String>(entry.event.id.to_hex())
+            .bind::<String>(entry.event.as_json())
+            .bind::<i64>(entry.queued_at.try_into()?)
+            .bind::<i64>(entry.attempts.into())
+            .bind::<i64>(entry.next_attempt_at.try_into()?)
+            .bind::<String>(serde_json::to_string(&entry.acked_relays)?)
+            .bind::<Option<String>>(entry.last_error.clone())
+            .execute(&context.db_pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    pub async fn remove(context: &Context, event_id: &EventId) -> Result<bool> {
+        let raw = format!(
+            "
+                DELETE FROM {}
+                WHERE id = ?
+            ",
+            Self::SQL_TABLE_NAME
+        );
+
+        let result = sqlx::query(&raw)
+            .bind::<String>(event_id.to_hex())
+            .execute(&context.db_pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Oldest first.
+    pub async fn get_all_entries(context: &Context) -> Result<Vec<OutboxEntry>> {
+        let raw = Self::select_sql("1");
+
+        let rows = sqlx::query(&raw).fetch_all(&context.db_pool).await?;
+
+        rows.iter().map(Self::entry_from_row).collect()
+    }
+
+    /// Entries whose backoff has passed, oldest first.
+    pub async fn get_due_entries(context: &Context) -> Result<Vec<OutboxEntry>> {
+        let raw = Self::select_sql("next_attempt_at <= ?");
+
+        let rows = sqlx::query(&raw)
+            .bind::<i64>(Timestamp::now().as_u64().try_into()?)
+            .fetch_all(&context.db_pool)
+            .await?;
+
+        rows.iter().map(Self::entry_from_row).collect()
+    }
+
+    async fn get_entry(context: &Context, event_id: &EventId) -> Result<Option<OutboxEntry>> {
+        let raw = Self::select_sql("id = ?");
+
+        let row = sqlx::query(&raw)
+            .bind::<String>(event_id.to_hex())
+            .fetch_optional(&context.db_pool)
+            .await?;
+
+        row.as_ref().map(Self::entry_from_row).transpose()
+    }
+
+    /// Values are bound to the `?` placeholders of the condition.
+    fn select_sql(condition: &str) -> String {
+        format!(
+            "
+                SELECT json, queued_at, attempts, next_attempt_at, acked_relays, last_error
+                FROM {}
+                WHERE {}
+                ORDER BY queued_at ASC
+            ",
+            Self::SQL_TABLE_NAME,
+            condition
+        )
+    }
+
+    fn entry_from_row(row: &SqliteRow) -> Result<OutboxEntry> {
+        Ok(OutboxEntry {
+            event: Event::from_json(row.get::<String, _>(0))?,
+            queued_at: row.get::<i64, _>(1).try_into()?,
+            attempts: row.get::<i64, _>(2).try_into()?,
+            next_attempt_at: row.get::<i64, _>(3).try_into()?,
+            acked_relays: serde_json::from_str(row.get(4))?,
+            last_error: row.get(5),
+        })
+    }
+
+    fn backoff_seconds(attempts: u32) -> u64 {
+        let exponent = attempts.saturating_sub(1).min(16);
+
+        (Self::BACKOFF_BASE_SECONDS << exponent).min(Self::BACKOFF_MAX_SECONDS)
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
 }
@@ -1,10 +1,21 @@
-use anyhow::Result;
+use std::cmp::Reverse;
+
+use anyhow::{Error, Result};
 use clap::Parser;
 use db::get_db;
+use futures_util::TryStreamExt;
+use nostr_sdk::{Filter, PublicKey};
 use parser::Cli;
+use prediction_market_event::{nostr_event_types::NostrEventUtils, EventHashHex};
 use sqlx::{Pool, Sqlite};
+use tokio::sync::OnceCell;
 
-use crate::{client::Signer, Client};
+use crate::{
+    client::{QueryOnly, Signer},
+    event_status::event_status_with,
+    pending_attestations::pending_attestations_with,
+    Client, ClientError, EventStatus, GetDetailedOutput, PendingAttestations,
+};
 
 pub mod db;
 pub mod parser;
@@ -12,27 +23,261 @@ pub mod stdin_prompts;
 
 pub struct Context {
     pub db_pool: Pool<Sqlite>,
+    /// Identity selected with `--identity`, overrides the active identity.
+    pub identity: Option<String>,
+    /// Connected on first use and shared by the whole command.
+    query_client: OnceCell<Client<QueryOnly>>,
+    client: OnceCell<Client<Signer>>,
 }
 impl Context {
-    pub async fn get() -> Result<Context> {
-        let context = Self {
-            db_pool: get_db().await?,
-        };
+    /// Events per request when querying relays page by page.
+    const PAGE_SIZE: usize = 500;
+
+    pub async fn get(identity: Option<String>) -> Result<Context> {
+        let context = Self::new(get_db().await?, identity);
 
         Ok(context)
     }
 
-    pub async fn client(&self) -> Result<Client<Signer>> {
-        let relays = db::NostrRelays::get_all_urls(self).await?;
-        let keys = db::NostrSecretKey::get_keys(self).await?;
+    /// Database has to be initialized with [db::initialize_db].
+    pub fn new(db_pool: Pool<Sqlite>, identity: Option<String>) -> Context {
+        Self {
+            db_pool,
+            identity,
+            query_client: OnceCell::new(),
+            client: OnceCell::new(),
+        }
+    }
+
+    /// Shares the relay connections of [Context::query_client].
+    pub async fn client(&self) -> Result<&Client<Signer>> {
+        self.client
+            .get_or_try_init(|| async {
+                let signer = db::NostrSecretKey::get_signer(self).await?;
+
+                Ok(self.query_client().await?.with_signer(signer))
+            })
+            .await
+    }
+
+    /// Does not read any keys.
+    pub async fn query_client(&self) -> Result<&Client<QueryOnly>> {
+        self.query_client
+            .get_or_try_init(|| async {
+                let relays = db::NostrRelays::get_enabled_relays(self).await?;
+
+                let client = Client::new_initialized_client_query_only(relays).await?;
+
+                Ok(client)
+            })
+            .await
+    }
+
+    /// Disconnects from relays if any client was used.
+    /// The signer client shares the relay connections and also disconnects a NIP-46 signer.
+    pub async fn disconnect(&self) -> Result<()> {
+        if let Some(client) = self.client.get() {
+            client.disconnect().await?;
+        } else if let Some(query_client) = self.query_client.get() {
+            query_client.disconnect().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Signer client without any relays, nothing is sent or received.
+    pub async fn offline_client(&self) -> Result<Client<Signer>> {
+        let signer = db::NostrSecretKey::get_signer(self).await?;
+
+        let client = Client::new_initialized_client_signer(Vec::new(), signer).await?;
+
+        Ok(client)
+    }
+
+    /// Answers from the local event cache and, unless offline, refreshes the cache from the relays.
+    pub async fn query_detailed<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        offline: bool,
+    ) -> Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let filters = filter_fn(PredictionMarketEventNostrEventType::filter());
+        let nostr_events = self.query_nostr_events(filters, offline, false).await?;
+
+        Ok(GetDetailedOutput::from_nostr_events(nostr_events))
+    }
+
+    /// Same as [Context::query_detailed] but relays are queried page by page so their result limits do not truncate the result.
+    /// Filter limits are ignored.
+    pub async fn query_all_detailed<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        offline: bool,
+    ) -> Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let filters = filter_fn(PredictionMarketEventNostrEventType::filter())
+            .into_iter()
+            .map(Filter::remove_limit)
+            .collect();
+        let nostr_events = self.query_nostr_events(filters, offline, true).await?;
+
+        Ok(GetDetailedOutput::from_nostr_events(nostr_events))
+    }
+
+    /// Events pledged to by the public key and not attested yet, see [Client::pending_attestations].
+    pub async fn pending_attestations(
+        &self,
+        public_key: PublicKey,
+        offline: bool,
+    ) -> Result<PendingAttestations> {
+        let client = match offline {
+            true => None,
+            false => Some(self.query_client().await?),
+        };
+
+        pending_attestations_with(public_key, move |filters| {
+            self.query_nostr_events_with(client, filters, false)
+        })
+        .await
+    }
+
+    async fn query_nostr_events(
+        &self,
+        filters: Vec<Filter>,
+        offline: bool,
+        paginate: bool,
+    ) -> Result<Vec<nostr_sdk::Event>> {
+        let client = match offline {
+            true => None,
+            false => Some(self.query_client().await?),
+        };
+
+        self.query_nostr_events_with(client, filters, paginate)
+            .await
+    }
+
+    /// Only the local event cache is queried without a client.
+    async fn query_nostr_events_with(
+        &self,
+        client: Option<&Client<QueryOnly>>,
+        filters: Vec<Filter>,
+        paginate: bool,
+    ) -> Result<Vec<nostr_sdk::Event>> {
+        let mut nostr_events = db::NostrEventCache::get_events(self, &filters).await?;
+        let Some(client) = client else {
+            return Ok(nostr_events);
+        };
+
+        let relay_nostr_events = match paginate {
+            true => {
+                let nostr_event_pages: Vec<Vec<nostr_sdk::Event>> = client
+                    .get_nostr_event_pages(filters.clone(), Self::PAGE_SIZE, None)
+                    .try_collect()
+                    .await?;
+                nostr_event_pages.into_iter().flatten().collect()
+            }
+            false => client.get_nostr_events(filters.clone(), None).await?,
+        };
+        db::NostrEventCache::put_events(self, &relay_nostr_events).await?;
+
+        for nostr_event in relay_nostr_events {
+            if !nostr_events.iter().any(|e| e.id == nostr_event.id) {
+                nostr_events.push(nostr_event);
+            }
+        }
+        nostr_events.sort_by_key(|event| Reverse(event.created_at));
+        if let [Filter {
+            limit: Some(limit), ..
+        }] = filters.as_slice()
+        {
+            nostr_events.truncate(*limit);
+        }
+
+        Ok(nostr_events)
+    }
+
+    pub async fn query<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        offline: bool,
+    ) -> Result<
+        Vec<(
+            nostr_sdk::Event,
+            PredictionMarketEventNostrEventType::InterpretResult,
+        )>,
+    >
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let output = self
+            .query_detailed::<PredictionMarketEventNostrEventType>(filter_fn, offline)
+            .await?;
+
+        Ok(output.accepted)
+    }
+
+    /// See [Client::event_status].
+    pub async fn event_status(
+        &self,
+        event_hash_hex: &EventHashHex,
+        offline: bool,
+    ) -> Result<EventStatus> {
+        let client = match offline {
+            true => None,
+            false => Some(self.query_client().await?),
+        };
 
-        Client::new_initialized_client_signer(relays, keys).await
+        event_status_with(event_hash_hex, move |filters| {
+            self.query_nostr_events_with(client, filters, false)
+        })
+        .await?
+        .ok_or(Error::msg("could not get event with hash hex"))
     }
 }
 
 pub async fn parse_and_handle() -> Result<serde_json::Value> {
-    let context = Context::get().await?;
     let cli = Cli::parse();
+    let context = Context::get(cli.identity.clone()).await?;
+
+    let result = cli.handle(&context).await;
+    // The command already succeeded or failed, a failed disconnect does not change its outcome.
+    if let Err(e) = context.disconnect().await {
+        eprintln!("WARNING: failed to disconnect: {e:#}");
+    }
+
+    result
+}
+
+/// Exit code when the command succeeded but its event was accepted by too few relays and saved to the outbox.
+pub const QUEUED_EXIT_CODE: i32 = 8;
+
+/// Exit code of a successful command, see [QUEUED_EXIT_CODE].
+pub fn success_exit_code(json: &serde_json::Value) -> i32 {
+    match json["queued"] == true {
+        true => QUEUED_EXIT_CODE,
+        false => 0,
+    }
+}
+
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    if let Some(client_error) = error.downcast_ref::<ClientError>() {
+        return match client_error {
+            ClientError::Relay(_) => 3,
+            ClientError::NotPublished(_) => 3,
+            ClientError::Signing(_) => 4,
+            ClientError::Interpretation(_) => 5,
+            ClientError::Validation(_) => 6,
+            ClientError::Timeout(_) => 7,
+            ClientError::Lagged(_) => 3,
+        };
+    }
+    if let Some(prediction_market_event::Error::Validation(_)) = error.downcast_ref() {
+        return 6;
+    }
 
-    cli.handle(&context).await
+    1
 }
@@ -1,21 +1,35 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{collections::HashSet, num::NonZeroUsize, slice, str::FromStr, time::Duration};
 
-use anyhow::{bail, Error, Result};
-use clap::{Parser, Subcommand};
-use nostr_sdk::{Filter, Keys, PublicKey, Timestamp, ToBech32, Url};
+use anyhow::{anyhow, bail, Error, Result};
+use chrono::{DateTime, Local};
+use clap::{Parser, Subcommand, ValueEnum};
+use futures_util::future;
+use nostr_sdk::{
+    nips::{nip46::NostrConnectURI, nip65::RelayMetadata},
+    EventId, Filter, JsonUtil, Keys, PublicKey, Timestamp, ToBech32, Url,
+};
 use prediction_market_event::{
-    information::Information,
+    information::{Information, V1},
     nostr_event_types::{
         EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
     },
     Event, EventHashHex, EventPayout, Outcome, PayoutUnit,
 };
+use serde::Deserialize;
 use serde_json::json;
 
-use crate::cli::{db, stdin_prompts, Context};
+use crate::{
+    cli::{db, stdin_prompts, Context},
+    Client, ClientError, Consensus, EventStatus, EventVerification, GetDetailedOutput,
+    Interpretation, Position, PublishReport, Quorum, RelayCheck, Settlement,
+};
 
 #[derive(Parser)]
 pub struct Cli {
+    /// Use this identity instead of the active one.
+    #[arg(long, global = true)]
+    pub identity: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -31,44 +45,242 @@ pub enum Commands {
         relay_commands: RelayCommands,
     },
     Publish {
+        /// Print the signed nostr event instead of sending it to relays.
+        /// New events are printed as `{"event": <signed nostr event>, "hash_hex": ...}`.
+        /// Events are only looked up in the local event cache.
+        #[arg(long, global = true)]
+        dry_run: bool,
+
         #[command(subcommand)]
         publish_commands: PublishCommands,
     },
+    /// Send signed nostr event json from a file, `-` for stdin, to relays.
+    /// The `event` of a new event dry run is sent too.
+    Broadcast { file: String },
+    /// Request deletion (NIP-09) of a nostr event published with the active identity, e.g. a pledge for the wrong event.
+    Retract {
+        nostr_event_id: EventId,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Check id, signature and content of signed nostr event json from a file, `-` for stdin.
+    /// No relays are contacted.
+    Verify { file: String },
+    /// Work with `Event` json without relays or keys.
+    Event {
+        #[command(subcommand)]
+        event_commands: EventCommands,
+    },
+    /// Events accepted by fewer relays than required when publishing.
+    Outbox {
+        #[command(subcommand)]
+        outbox_commands: OutboxCommands,
+    },
+    /// Payout of an event according to the consensus of its pledgers.
+    /// Remainders lost by rounding down are in 1/units_to_payout.
+    Settle {
+        event_hash_hex: EventHashHex,
+        #[arg(short, long)]
+        quorum: usize,
+        /// Json array of `{"holder": ..., "outcome": ..., "units": ...}` from a file, `-` for stdin.
+        #[arg(long)]
+        positions: Option<String>,
+        /// Answer from the local event cache only.
+        #[arg(long)]
+        offline: bool,
+    },
     Query {
+        /// Answer from the local event cache only.
+        #[arg(long, global = true)]
+        offline: bool,
+
         #[command(subcommand)]
         query_commands: QueryCommands,
     },
 }
 
+#[derive(Subcommand)]
+pub enum EventCommands {
+    /// Validate `Event` json from a file, `-` for stdin, and print its hash hex.
+    Hash { file: String },
+    /// Print outcomes, payout date and nonce of `Event` json from a file, `-` for stdin.
+    Inspect { file: String },
+}
+
+#[derive(Subcommand)]
+pub enum OutboxCommands {
+    List,
+    /// Send events again whose retry backoff has passed.
+    /// Events are removed once accepted by the minimum number of relays.
+    Flush {
+        /// Ignore the retry backoff.
+        #[arg(long)]
+        all: bool,
+    },
+    Remove {
+        event_id: EventId,
+    },
+    /// Show or set the number of relays that have to accept an event for a publish to count as successful.
+    MinAcks {
+        /// At least 1.
+        min_acks: Option<NonZeroUsize>,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum KeyCommand {
     Public,
     Secret,
-    Set { secret_key: String },
+    /// Generate a new key for the active identity.
+    Generate {
+        /// Derive the key from a new BIP-39 mnemonic (NIP-06).
+        #[arg(long)]
+        mnemonic: bool,
+    },
+    /// Derive key from a BIP-39 mnemonic (NIP-06) and store it for the active identity.
+    /// The mnemonic is read from PREDICTION_MARKET_EVENT_CLI_MNEMONIC if set, otherwise prompted.
+    ImportMnemonic {
+        /// Ask for the BIP-39 passphrase, not stored.
+        /// Read from PREDICTION_MARKET_EVENT_CLI_BIP39_PASSPHRASE if set.
+        #[arg(long)]
+        passphrase: bool,
+        #[arg(long, default_value_t = 0)]
+        account: u32,
+        /// Replace the key the identity already has.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the mnemonic the key was derived from.
+    ExportMnemonic,
+    Set {
+        secret_key: String,
+    },
     Delete,
+    /// Add identity, a new key is generated if no secret key is given.
+    Add {
+        name: String,
+        #[arg(long)]
+        secret_key: Option<String>,
+    },
+    List,
+    /// Use a NIP-46 remote signer for the active identity.
+    Connect {
+        /// bunker://<remote signer public key>?relay=<relay>&secret=<secret>
+        bunker_uri: String,
+    },
+    /// Make identity the active one.
+    Use {
+        name: String,
+    },
+    /// Encrypt stored key with a passphrase (NIP-49), the stored mnemonic too.
+    Encrypt,
+    /// Store key unencrypted.
+    Decrypt,
+    /// The new passphrase is read from PREDICTION_MARKET_EVENT_CLI_NEW_PASSPHRASE if set.
+    ChangePassphrase,
 }
 
 #[derive(Subcommand)]
 pub enum RelayCommands {
-    Add { url: String },
-    Remove { url: String },
+    Add {
+        url: String,
+        #[arg(long, value_enum, default_value_t = RelayRole::Both)]
+        role: RelayRole,
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    Remove {
+        url: String,
+    },
     RemoveAll,
     ListAll,
+    /// Set whether the relay is used for reading, writing or both.
+    SetRole {
+        url: String,
+        #[arg(value_enum)]
+        role: RelayRole,
+    },
+    Enable {
+        url: String,
+    },
+    /// Keep the relay but stop using it.
+    Disable {
+        url: String,
+    },
     AddRecommendedRelayList,
+    /// Publish enabled relays as NIP-65 relay list (kind 10002).
+    PublishList,
+    /// Connect to every relay and report latency, NIP-11 info and whether prediction market event kinds are accepted and returned.
+    /// Sends a throwaway probe event of each kind that expires after a minute.
+    Check {
+        /// Remove relays that could not be connected to.
+        #[arg(long)]
+        prune: bool,
+        /// Seconds to wait for each step of the check.
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
+    },
+    /// Add relays from the NIP-65 relay list of a public key, already added relays are left as they are.
+    ImportNip65 {
+        public_key: PublicKey,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RelayRole {
+    Read,
+    Write,
+    Both,
+}
+
+impl RelayRole {
+    fn relay_metadata(self) -> Option<RelayMetadata> {
+        match self {
+            Self::Read => Some(RelayMetadata::Read),
+            Self::Write => Some(RelayMetadata::Write),
+            Self::Both => None,
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum PublishCommands {
     NewEvent {
-        outcome_count: Outcome,
-        units_to_payout: PayoutUnit,
-        information_type: String,
+        #[arg(required_unless_present = "from_file")]
+        outcome_count: Option<Outcome>,
+        #[arg(required_unless_present = "from_file")]
+        units_to_payout: Option<PayoutUnit>,
+        #[arg(required_unless_present = "from_file", conflicts_with = "from_file")]
+        information_type: Option<String>,
+
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Repeat once per outcome, in outcome order.
+        #[arg(long = "outcome-title")]
+        outcome_titles: Vec<String>,
+        /// Expected payout date time UTC (format: `2023-10-01T12:00:00Z`).
+        #[arg(long)]
+        expected_payout: Option<String>,
+
+        /// Read the full `Event` or its `Information` as json from a file, `-` for stdin.
+        /// Outcome count and units to payout are only given for `Information`.
+        #[arg(long, conflicts_with_all = ["title", "description", "outcome_titles", "expected_payout"])]
+        from_file: Option<String>,
     },
     FutureEventPayoutAttestationPledge {
         event_hash_hex: EventHashHex,
     },
     EventPayoutAttestation {
         event_hash_hex: EventHashHex,
+
+        /// Units paid to each outcome, in outcome order (example: `0,100,0`).
+        #[arg(long, value_delimiter = ',', conflicts_with = "winning_outcome")]
+        units_per_outcome: Option<Vec<PayoutUnit>>,
+        /// Outcome index or title that receives all units.
+        #[arg(long)]
+        winning_outcome: Option<String>,
     },
 }
 
@@ -85,12 +297,30 @@ pub enum QueryCommands {
         until: Option<Timestamp>,
         #[arg(short, long)]
         event_hash_hex: Option<EventHashHex>,
+        #[arg(long)]
+        show_rejected: bool,
+        /// Fail with exit code 5 if any received event can not be interpreted.
+        #[arg(long, conflicts_with = "show_rejected")]
+        strict: bool,
+        /// Query relays page by page until all matching events are received, not truncated by relay result limits.
+        #[arg(long, conflicts_with = "limit")]
+        all: bool,
 
         #[command(subcommand)]
         query_custom_commands: QueryCustomCommands,
     },
     MyCreatedEvents,
+    /// Events you pledged to attest to and have not attested to yet.
+    /// Relay responses that were ignored are printed as warnings to stderr.
     EventsPendingYourAttestation,
+    EventStatus {
+        event_hash_hex: EventHashHex,
+    },
+    Consensus {
+        event_hash_hex: EventHashHex,
+        #[arg(short, long)]
+        quorum: usize,
+    },
 }
 
 #[derive(Subcommand)]
@@ -111,15 +341,62 @@ impl Cli {
                 key_commands: keys_commands,
             } => match keys_commands {
                 KeyCommand::Public => {
-                    let keys = db::NostrSecretKey::get_keys(context).await?;
+                    let public_key = db::NostrSecretKey::get_public_key(context).await?;
 
-                    json!(keys.public_key.to_bech32()?)
+                    json!(public_key.to_bech32()?)
                 }
                 KeyCommand::Secret => {
                     let keys = db::NostrSecretKey::get_keys(context).await?;
 
                     json!(keys.secret_key().to_bech32()?)
                 }
+                KeyCommand::Generate { mnemonic } => match mnemonic {
+                    true => {
+                        let (keys, mnemonic_backup) =
+                            db::NostrSecretKey::generate_mnemonic_keys(context).await?;
+
+                        json!({
+                            "public_key": keys.public_key.to_bech32()?,
+                            "mnemonic": mnemonic_backup.mnemonic,
+                            "warning": "Write down the mnemonic, it is the only way to recover the key if the local database is lost.",
+                        })
+                    }
+                    false => {
+                        let keys = db::NostrSecretKey::generate_keys(context).await?;
+
+                        generated_keys_json(&keys)?
+                    }
+                },
+                KeyCommand::ImportMnemonic {
+                    passphrase,
+                    account,
+                    force,
+                } => {
+                    let mnemonic = stdin_prompts::mnemonic_prompt()?;
+                    let passphrase = match passphrase {
+                        true => Some(stdin_prompts::bip39_passphrase_prompt()?),
+                        false => None,
+                    };
+                    let keys = db::NostrSecretKey::import_mnemonic(
+                        context,
+                        &mnemonic,
+                        passphrase.as_deref(),
+                        account,
+                        force,
+                    )
+                    .await?;
+
+                    json!(keys.public_key.to_bech32()?)
+                }
+                KeyCommand::ExportMnemonic => {
+                    let mnemonic_backup = db::NostrSecretKey::export_mnemonic(context).await?;
+
+                    json!({
+                        "mnemonic": mnemonic_backup.mnemonic,
+                        "account": mnemonic_backup.account,
+                        "passphrase_required": mnemonic_backup.passphrase,
+                    })
+                }
                 KeyCommand::Set { secret_key } => {
                     let keys = Keys::parse(secret_key)?;
                     db::NostrSecretKey::set_keys(context, Some(&keys)).await?;
@@ -129,15 +406,81 @@ impl Cli {
                 KeyCommand::Delete => {
                     db::NostrSecretKey::set_keys(context, None).await?;
 
+                    json!(true)
+                }
+                KeyCommand::Add { name, secret_key } => match secret_key {
+                    Some(secret_key) => {
+                        let keys = Keys::parse(secret_key)?;
+                        db::NostrSecretKey::add_identity(context, &name, &keys).await?;
+
+                        json!(keys.public_key.to_bech32()?)
+                    }
+                    None => {
+                        let keys = Keys::generate();
+                        db::NostrSecretKey::add_identity(context, &name, &keys).await?;
+
+                        generated_keys_json(&keys)?
+                    }
+                },
+                KeyCommand::List => {
+                    let active_identity = db::NostrSecretKey::active_identity(context).await?;
+                    let mut identities = Vec::new();
+                    for identity in db::NostrSecretKey::list_identities(context).await? {
+                        let public_key = match identity.public_key {
+                            Some(public_key) => Some(public_key.to_bech32()?),
+                            None => None,
+                        };
+                        identities.push(json!({
+                            "name": identity.name,
+                            "active": identity.name == active_identity,
+                            "encrypted": identity.encrypted,
+                            "remote": identity.remote,
+                            "public_key": public_key,
+                            "mnemonic": identity.mnemonic,
+                        }));
+                    }
+
+                    json!(identities)
+                }
+                KeyCommand::Connect { bunker_uri } => {
+                    let uri = NostrConnectURI::parse(bunker_uri)?;
+                    let public_key = db::NostrSecretKey::connect(context, uri).await?;
+
+                    json!(public_key.to_bech32()?)
+                }
+                KeyCommand::Use { name } => {
+                    db::NostrSecretKey::use_identity(context, &name).await?;
+
+                    json!(true)
+                }
+                KeyCommand::Encrypt => {
+                    let passphrase = stdin_prompts::new_passphrase_prompt()?;
+                    db::NostrSecretKey::encrypt_keys(context, &passphrase).await?;
+
+                    json!(true)
+                }
+                KeyCommand::Decrypt => {
+                    let passphrase = stdin_prompts::passphrase_prompt()?;
+                    db::NostrSecretKey::decrypt_keys(context, &passphrase).await?;
+
+                    json!(true)
+                }
+                KeyCommand::ChangePassphrase => {
+                    let passphrase = stdin_prompts::passphrase_prompt()?;
+                    let new_passphrase = stdin_prompts::new_passphrase_prompt()?;
+                    db::NostrSecretKey::change_passphrase(context, &passphrase, &new_passphrase)
+                        .await?;
+
                     json!(true)
                 }
             },
             Commands::Relay { relay_commands } => match relay_commands {
-                RelayCommands::Add { url } => {
+                RelayCommands::Add { url, role, notes } => {
                     let url = Url::from_str(&url)?;
-                    db::NostrRelays::add_url(context, url).await?;
+                    let relay_info = db::RelayInfo::new(role.relay_metadata(), notes);
+                    let added = db::NostrRelays::add_url(context, url, relay_info).await?;
 
-                    json!(true)
+                    json!(added)
                 }
                 RelayCommands::Remove { url } => {
                     let url = Url::from_str(&url)?;
@@ -151,89 +494,393 @@ impl Cli {
                     json!(true)
                 }
                 RelayCommands::ListAll => {
-                    let all_urls = db::NostrRelays::get_all_urls(context).await?;
+                    let all_relays = db::NostrRelays::get_all_relays(context).await?;
 
-                    json!(all_urls)
+                    json!(all_relays
+                        .into_iter()
+                        .map(|(url, relay_info)| json!({
+                            "url": url,
+                            "read": relay_info.read,
+                            "write": relay_info.write,
+                            "enabled": relay_info.enabled,
+                            "added_at": relay_info.added_at,
+                            "notes": relay_info.notes,
+                        }))
+                        .collect::<Vec<_>>())
+                }
+                RelayCommands::SetRole { url, role } => {
+                    let url = Url::from_str(&url)?;
+                    db::NostrRelays::set_role(context, url, role.relay_metadata()).await?;
+
+                    json!(true)
+                }
+                RelayCommands::Enable { url } => {
+                    let url = Url::from_str(&url)?;
+                    db::NostrRelays::set_enabled(context, url, true).await?;
+
+                    json!(true)
+                }
+                RelayCommands::Disable { url } => {
+                    let url = Url::from_str(&url)?;
+                    db::NostrRelays::set_enabled(context, url, false).await?;
+
+                    json!(true)
                 }
                 RelayCommands::AddRecommendedRelayList => {
                     for relay in RECOMMENDED_RELAY_LIST {
-                        db::NostrRelays::add_url(context, Url::from_str(&relay)?).await?;
+                        let relay_info = db::RelayInfo::new(None, None);
+                        db::NostrRelays::add_url(context, Url::from_str(relay)?, relay_info)
+                            .await?;
                     }
 
                     json!(true)
                 }
+                RelayCommands::PublishList => {
+                    let relays = db::NostrRelays::get_enabled_relays(context).await?;
+                    if relays.is_empty() {
+                        bail!("no enabled relays to publish");
+                    }
+                    let report = context.client().await?.publish_relay_list(relays).await?;
+
+                    publish_report_json(&report)
+                }
+                RelayCommands::Check { prune, timeout } => {
+                    let relays = db::NostrRelays::get_all_relays(context).await?;
+                    let relay_checks =
+                        future::join_all(relays.into_iter().map(|(url, _)| {
+                            Client::check_relay(url, Duration::from_secs(timeout))
+                        }))
+                        .await;
+
+                    let mut pruned_urls = Vec::new();
+                    if prune {
+                        for relay_check in relay_checks.iter().filter(|r| !r.connected) {
+                            db::NostrRelays::remove_url(context, relay_check.url.clone()).await?;
+                            pruned_urls.push(relay_check.url.clone());
+                        }
+                    }
+
+                    json!({
+                        "relays": relay_checks.iter().map(relay_check_json).collect::<Vec<_>>(),
+                        "pruned": pruned_urls,
+                    })
+                }
+                RelayCommands::ImportNip65 { public_key } => {
+                    let Some(relay_list) = context
+                        .query_client()
+                        .await?
+                        .get_relay_list(public_key, None)
+                        .await?
+                    else {
+                        bail!("no relay list found for public key");
+                    };
+
+                    let notes = format!("imported from relay list of {}", public_key.to_bech32()?);
+                    let mut added_urls = Vec::new();
+                    for (url, relay_metadata) in relay_list {
+                        let relay_info = db::RelayInfo::new(relay_metadata, Some(notes.clone()));
+                        if db::NostrRelays::add_url(context, url.clone(), relay_info).await? {
+                            added_urls.push(url);
+                        }
+                    }
+
+                    json!(added_urls)
+                }
             },
 
-            Commands::Publish { publish_commands } => match publish_commands {
+            Commands::Publish {
+                dry_run,
+                publish_commands,
+            } => match publish_commands {
                 PublishCommands::NewEvent {
                     outcome_count,
                     units_to_payout,
                     information_type,
+                    title,
+                    description,
+                    outcome_titles,
+                    expected_payout,
+                    from_file,
                 } => {
-                    let information = stdin_prompts::information_creator_prompt(
-                        &information_type,
-                        outcome_count,
-                    )?;
-                    let event =
-                        Event::new_with_random_nonce(outcome_count, units_to_payout, information);
+                    let event = match from_file {
+                        Some(path) => {
+                            let json = stdin_prompts::read_file_or_stdin(&path)?;
+                            match Event::try_from_json_str(&json) {
+                                Ok(event) => {
+                                    if outcome_count.is_some() || units_to_payout.is_some() {
+                                        bail!("outcome count and units to payout can not be given when file contains the full event");
+                                    }
+
+                                    event
+                                }
+                                Err(event_error) => {
+                                    let information: Information = serde_json::from_str(&json)
+                                        .map_err(|information_error| {
+                                            anyhow!("file is neither an event ({event_error}) nor information ({information_error})")
+                                        })?;
+                                    let (Some(outcome_count), Some(units_to_payout)) =
+                                        (outcome_count, units_to_payout)
+                                    else {
+                                        bail!("outcome count and units to payout are required when file only contains information");
+                                    };
+
+                                    Event::new_with_random_nonce(
+                                        outcome_count,
+                                        units_to_payout,
+                                        information,
+                                    )
+                                }
+                            }
+                        }
+                        None => {
+                            let (
+                                Some(outcome_count),
+                                Some(units_to_payout),
+                                Some(information_type),
+                            ) = (outcome_count, units_to_payout, information_type)
+                            else {
+                                bail!("outcome count, units to payout and information type are required");
+                            };
+                            let information = if title.is_none()
+                                && description.is_none()
+                                && outcome_titles.is_empty()
+                                && expected_payout.is_none()
+                            {
+                                stdin_prompts::information_creator_prompt(
+                                    &information_type,
+                                    outcome_count,
+                                )?
+                            } else {
+                                information_from_args(
+                                    &information_type,
+                                    title,
+                                    description,
+                                    outcome_titles,
+                                    expected_payout,
+                                )?
+                            };
+
+                            Event::new_with_random_nonce(
+                                outcome_count,
+                                units_to_payout,
+                                information,
+                            )
+                        }
+                    };
                     event.validate(Information::ALL_VARIANT_IDS)?;
                     let event_hash_hex = event.hash_hex()?;
 
-                    let success = context.client().await?.publish::<NewEvent>(&event).await?;
+                    let mut json = publish::<NewEvent>(context, &event, dry_run).await?;
+                    match dry_run {
+                        // The signed nostr event stays untouched so it can be broadcast as is.
+                        true => json = json!({"event": json, "hash_hex": event_hash_hex}),
+                        false => json["hash_hex"] = json!(event_hash_hex),
+                    }
 
-                    json!({
-                        "hash_hex": event_hash_hex,
-                        "relays": success,
-                    })
+                    json
                 }
                 PublishCommands::FutureEventPayoutAttestationPledge { event_hash_hex } => {
-                    let success = context
-                        .client()
+                    publish::<FutureEventPayoutAttestationPledge>(context, &event_hash_hex, dry_run)
                         .await?
-                        .publish::<FutureEventPayoutAttestationPledge>(&event_hash_hex)
-                        .await?;
-
-                    json!({
-                        "relays": success,
-                    })
                 }
-                PublishCommands::EventPayoutAttestation { event_hash_hex } => {
+                PublishCommands::EventPayoutAttestation {
+                    event_hash_hex,
+                    units_per_outcome,
+                    winning_outcome,
+                } => {
                     let res = context
-                        .client()
-                        .await?
-                        .get::<NewEvent>(|f| vec![f.hashtag(event_hash_hex.0.to_owned())], None)
+                        .query::<NewEvent>(
+                            |f| vec![f.hashtag(event_hash_hex.0.to_owned())],
+                            dry_run,
+                        )
                         .await?;
                     let event = res
-                        .get(0)
+                        .first()
                         .map(|(_, e)| e)
                         .ok_or(Error::msg("could not get event with hash hex"))?;
-                    let units_per_outcome =
-                        stdin_prompts::event_payout_units_per_outcome_creator_prompt(event)?;
+                    let units_per_outcome = match (units_per_outcome, winning_outcome) {
+                        (Some(units_per_outcome), _) => units_per_outcome,
+                        (None, Some(winning_outcome)) => {
+                            winning_outcome_units_per_outcome(event, &winning_outcome)?
+                        }
+                        (None, None) => {
+                            stdin_prompts::event_payout_units_per_outcome_creator_prompt(event)?
+                        }
+                    };
                     let event_payout = EventPayout {
                         event_hash_hex,
                         units_per_outcome,
                     };
                     event_payout.validate(event)?;
 
-                    let success = context
-                        .client()
-                        .await?
-                        .publish::<EventPayoutAttestation>(&event_payout)
-                        .await?;
+                    publish::<EventPayoutAttestation>(context, &event_payout, dry_run).await?
+                }
+            },
 
-                    json!({
-                        "relays": success,
-                    })
+            Commands::Broadcast { file } => {
+                let json: serde_json::Value =
+                    serde_json::from_str(&stdin_prompts::read_file_or_stdin(&file)?)?;
+                // Dry runs of new events wrap the signed nostr event together with its hash_hex.
+                let json = match json.get("event") {
+                    Some(nostr_event_json) => nostr_event_json.to_owned(),
+                    None => json,
+                };
+                let nostr_event = nostr_sdk::Event::from_json(json.to_string())?;
+                // Parsing does not check the id and signature, relays would reject the event anyway.
+                let event_verification = EventVerification::new(&nostr_event);
+                if !event_verification.id_valid {
+                    bail!(
+                        "nostr event id {} does not match its content",
+                        nostr_event.id
+                    )
+                }
+                if !event_verification.signature_valid {
+                    bail!("nostr event {} has an invalid signature", nostr_event.id)
+                }
+                let client = context.query_client().await?;
+
+                broadcast_or_queue(context, client, nostr_event).await?
+            }
+
+            Commands::Retract {
+                nostr_event_id,
+                reason,
+            } => {
+                let client = context.client().await?;
+                let nostr_event = client
+                    .sign_deletion([nostr_event_id], reason.as_deref())
+                    .await?;
+                db::NostrEventCache::put_events(context, slice::from_ref(&nostr_event)).await?;
+
+                broadcast_or_queue(context, client, nostr_event).await?
+            }
+
+            Commands::Verify { file } => {
+                let json = stdin_prompts::read_file_or_stdin(&file)?;
+                let nostr_event = nostr_sdk::Event::from_json(json)?;
+                let event_verification = EventVerification::new(&nostr_event);
+
+                event_verification_json(&nostr_event, &event_verification)
+            }
+
+            Commands::Event { event_commands } => match event_commands {
+                EventCommands::Hash { file } => {
+                    let json = stdin_prompts::read_file_or_stdin(&file)?;
+                    let event = Event::try_from_json_str(&json)?;
+                    event.validate(Information::ALL_VARIANT_IDS)?;
+
+                    json!({"event_hash_hex": event.hash_hex()?})
+                }
+                EventCommands::Inspect { file } => {
+                    let json = stdin_prompts::read_file_or_stdin(&file)?;
+                    let event = Event::try_from_json_str(&json)?;
+
+                    event_inspect_json(&event)?
                 }
             },
 
-            Commands::Query { query_commands } => match query_commands {
+            Commands::Outbox { outbox_commands } => match outbox_commands {
+                OutboxCommands::List => {
+                    let entries = db::Outbox::get_all_entries(context).await?;
+
+                    json!(entries.iter().map(outbox_entry_json).collect::<Vec<_>>())
+                }
+                OutboxCommands::Flush { all } => {
+                    let entries = match all {
+                        true => db::Outbox::get_all_entries(context).await?,
+                        false => db::Outbox::get_due_entries(context).await?,
+                    };
+                    let min_relay_acks = db::Settings::get_min_relay_acks(context).await?;
+                    let client = context.query_client().await?;
+
+                    let mut results = Vec::new();
+                    for entry in entries {
+                        let (acked_relays, error) =
+                            match broadcast_with_report(client, entry.event.clone()).await {
+                                Ok(report) => {
+                                    let error = failed_relays_error(&report);
+                                    (report.success, error)
+                                }
+                                Err(e) => (HashSet::new(), Some(error_chain(&e))),
+                            };
+
+                        let all_acked_relays: HashSet<Url> =
+                            entry.acked_relays.union(&acked_relays).cloned().collect();
+                        if all_acked_relays.len() >= min_relay_acks {
+                            db::Outbox::remove(context, &entry.event.id).await?;
+                            results.push(json!({
+                                "event_id": entry.event.id.to_hex(),
+                                "sent": true,
+                                "acked_relays": all_acked_relays,
+                            }));
+                        } else {
+                            let entry = db::Outbox::record_attempt(
+                                context,
+                                &entry.event,
+                                &acked_relays,
+                                error,
+                            )
+                            .await?;
+                            let mut result = outbox_entry_json(&entry);
+                            result["sent"] = json!(false);
+                            results.push(result);
+                        }
+                    }
+
+                    json!(results)
+                }
+                OutboxCommands::Remove { event_id } => {
+                    let removed = db::Outbox::remove(context, &event_id).await?;
+
+                    json!(removed)
+                }
+                OutboxCommands::MinAcks { min_acks } => {
+                    if let Some(min_acks) = min_acks {
+                        db::Settings::set_min_relay_acks(context, min_acks).await?;
+                    }
+
+                    json!(db::Settings::get_min_relay_acks(context).await?)
+                }
+            },
+
+            Commands::Settle {
+                event_hash_hex,
+                quorum,
+                positions,
+                offline,
+            } => {
+                let positions: Vec<Position> = match positions {
+                    Some(path) => {
+                        let json = stdin_prompts::read_file_or_stdin(&path)?;
+                        let positions: Vec<PositionJson> = serde_json::from_str(&json)?;
+
+                        positions.into_iter().map(Position::from).collect()
+                    }
+                    None => Vec::new(),
+                };
+                let event_status = context.event_status(&event_hash_hex, offline).await?;
+                let consensus = event_status.consensus(Quorum { k: quorum });
+                let Some(winning_payout) = &consensus.winning_payout else {
+                    bail!("no consensus on the payout of event {event_hash_hex}");
+                };
+                let settlement = Settlement::new(&event_status.event, winning_payout, &positions)?;
+
+                settlement_json(&event_status.event, &consensus, &settlement)?
+            }
+
+            Commands::Query {
+                offline,
+                query_commands,
+            } => match query_commands {
                 QueryCommands::Custom {
                     author,
                     limit,
                     since,
                     until,
                     event_hash_hex,
+                    show_rejected,
+                    strict,
+                    all,
                     query_custom_commands,
                 } => {
                     let filter_fn = |mut f: Filter| {
@@ -258,101 +905,73 @@ impl Cli {
 
                     match query_custom_commands {
                         QueryCustomCommands::NewEvent => {
-                            let res = context
-                                .client()
-                                .await?
-                                .get::<NewEvent>(filter_fn, None)
-                                .await?;
+                            let res =
+                                query_custom::<NewEvent>(context, filter_fn, offline, all).await?;
 
-                            new_event_json(&res)
+                            with_rejected_json(new_event_json, res, show_rejected, strict)?
                         }
                         QueryCustomCommands::FutureEventPayoutAttestationPledge => {
-                            let res = context
-                                .client()
-                                .await?
-                                .get::<FutureEventPayoutAttestationPledge>(filter_fn, None)
-                                .await?;
+                            let res = query_custom::<FutureEventPayoutAttestationPledge>(
+                                context, filter_fn, offline, all,
+                            )
+                            .await?;
 
-                            future_event_payout_attestation_pledge_json(&res)
+                            with_rejected_json(
+                                future_event_payout_attestation_pledge_json,
+                                res,
+                                show_rejected,
+                                strict,
+                            )?
                         }
                         QueryCustomCommands::EventPayoutAttestation => {
-                            let res = context
-                                .client()
-                                .await?
-                                .get::<EventPayoutAttestation>(filter_fn, None)
-                                .await?;
+                            let res = query_custom::<EventPayoutAttestation>(
+                                context, filter_fn, offline, all,
+                            )
+                            .await?;
 
-                            event_payout_attestation_json(&res)
+                            with_rejected_json(
+                                event_payout_attestation_json,
+                                res,
+                                show_rejected,
+                                strict,
+                            )?
                         }
                     }
                 }
                 QueryCommands::MyCreatedEvents => {
-                    let author = db::NostrSecretKey::get_keys(context).await?.public_key;
+                    let author = db::NostrSecretKey::get_public_key(context).await?;
 
                     let res = context
-                        .client()
-                        .await?
-                        .get::<NewEvent>(|f| vec![f.author(author).limit(100)], None)
+                        .query::<NewEvent>(|f| vec![f.author(author).limit(100)], offline)
                         .await?;
 
                     new_event_json(&res)
                 }
                 QueryCommands::EventsPendingYourAttestation => {
-                    let author = db::NostrSecretKey::get_keys(context).await?.public_key;
+                    let author = db::NostrSecretKey::get_public_key(context).await?;
 
-                    let events_with_future_event_payout_attestation_pledge: HashSet<_> = context
-                        .client()
-                        .await?
-                        .get::<FutureEventPayoutAttestationPledge>(|f| vec![f.author(author)], None)
-                        .await?
-                        .into_iter()
-                        .map(|(_, (pk, event))| {
-                            assert_eq!(author.to_hex(), pk.0);
-                            event
-                        })
-                        .collect();
+                    let pending_attestations =
+                        context.pending_attestations(author, offline).await?;
 
-                    let events_with_event_payout_attestation: HashSet<_> = context
-                        .client()
-                        .await?
-                        .get::<EventPayoutAttestation>(
-                            |f| {
-                                events_with_future_event_payout_attestation_pledge
-                                    .iter()
-                                    .map(|event_hash_hex| {
-                                        f.clone().author(author).hashtag(&event_hash_hex.0)
-                                    })
-                                    .collect()
-                            },
-                            None,
-                        )
-                        .await?
-                        .into_iter()
-                        .map(|(_, (pk, event_payout))| {
-                            assert_eq!(author.to_hex(), pk.0);
-                            event_payout.event_hash_hex
-                        })
-                        .collect();
-
-                    let events_pending_attestation: Vec<_> =
-                        events_with_future_event_payout_attestation_pledge
-                            .difference(&events_with_event_payout_attestation)
-                            .collect();
-                    let res: Vec<_> = context
-                        .client()
-                        .await?
-                        .get::<NewEvent>(
-                            |f| {
-                                events_pending_attestation
-                                    .iter()
-                                    .map(|event_hash_hex| f.clone().hashtag(&event_hash_hex.0))
-                                    .collect()
-                            },
-                            None,
-                        )
-                        .await?;
+                    for warning in &pending_attestations.warnings {
+                        eprintln!("WARNING: {warning}");
+                    }
 
-                    new_event_json(&res)
+                    new_event_json(&pending_attestations.events)
+                }
+                QueryCommands::EventStatus { event_hash_hex } => {
+                    let event_status = context.event_status(&event_hash_hex, offline).await?;
+
+                    event_status_json(&event_hash_hex, &event_status)
+                }
+                QueryCommands::Consensus {
+                    event_hash_hex,
+                    quorum,
+                } => {
+                    let event_status = context.event_status(&event_hash_hex, offline).await?;
+                    let consensus = event_status.consensus(Quorum { k: quorum });
+
+                    consensus_json(&consensus)
                 }
             },
         };
@@ -361,6 +980,68 @@ impl Cli {
     }
 }
 
+fn information_from_args(
+    information_type: &str,
+    title: Option<String>,
+    description: Option<String>,
+    outcome_titles: Vec<String>,
+    expected_payout: Option<String>,
+) -> Result<Information> {
+    if information_type.to_ascii_lowercase() != V1::ID {
+        bail!(
+            "information arguments are only supported for information type {}",
+            V1::ID
+        );
+    }
+    let (Some(title), Some(expected_payout)) = (title, expected_payout) else {
+        bail!("--title and --expected-payout are required");
+    };
+
+    Ok(Information::V1(V1 {
+        title,
+        description: description.unwrap_or_default(),
+        outcome_titles,
+        expected_payout_unix_seconds: stdin_prompts::parse_expected_payout(&expected_payout)?,
+    }))
+}
+
+/// Outcome is matched by index first and by [V1] outcome title second.
+fn winning_outcome_units_per_outcome(
+    event: &Event,
+    winning_outcome: &str,
+) -> Result<Vec<PayoutUnit>> {
+    let outcome = match winning_outcome.parse::<Outcome>() {
+        Ok(outcome) if outcome < event.outcome_count => outcome,
+        _ => {
+            let Information::V1(v1) = &event.information else {
+                bail!("event has no outcome titles, winning outcome must be an index");
+            };
+            let Some(outcome) = v1
+                .outcome_titles
+                .iter()
+                .position(|outcome_title| outcome_title == winning_outcome)
+            else {
+                bail!("no outcome matches winning outcome {winning_outcome}");
+            };
+            // Events from relays are not validated and may have more titles than outcomes.
+            let outcome: Outcome = outcome.try_into()?;
+            if outcome >= event.outcome_count {
+                bail!(
+                    "winning outcome {winning_outcome} is outcome {outcome} but event only has {} outcomes",
+                    event.outcome_count
+                );
+            }
+
+            outcome
+        }
+    };
+
+    let mut units_per_outcome = vec![0; event.outcome_count.into()];
+    units_per_outcome[usize::from(outcome)] = event.units_to_payout;
+
+    Ok(units_per_outcome)
+}
+
 fn new_event_json(
     res: &Vec<(
         nostr_sdk::Event,
@@ -406,6 +1087,413 @@ fn event_payout_attestation_json(
     json!(events)
 }
 
+fn event_status_json(
+    event_hash_hex: &EventHashHex,
+    event_status: &EventStatus,
+) -> serde_json::Value {
+    let attestations: Vec<_> = event_status
+        .attestations
+        .iter()
+        .map(|(attestor, event_payout)| {
+            json!({"attestor": attestor, "units_per_outcome": event_payout.units_per_outcome})
+        })
+        .collect();
+    let invalid_attestations: Vec<_> = event_status
+        .invalid_attestations
+        .iter()
+        .map(|(attestor, event_payout, e)| {
+            json!({
+                "attestor": attestor,
+                "units_per_outcome": event_payout.units_per_outcome,
+                "error": e.to_string(),
+            })
+        })
+        .collect();
+
+    json!({
+        "event_hash_hex": event_hash_hex,
+        "event": event_status.event,
+        "pledgers": event_status.pledgers,
+        "attestations": attestations,
+        "invalid_attestations": invalid_attestations,
+        "pending_pledgers": event_status.pending_pledgers,
+    })
+}
+
+fn consensus_json(consensus: &Consensus) -> serde_json::Value {
+    let groups: Vec<_> = consensus
+        .groups
+        .iter()
+        .map(|group| {
+            json!({
+                "units_per_outcome": group.event_payout.units_per_outcome,
+                "attestors": group.attestors,
+            })
+        })
+        .collect();
+
+    json!({
+        "reached": consensus.is_reached(),
+        "winning_payout": consensus.winning_payout,
+        "groups": groups,
+        "conflicting_pledgers": consensus.conflicting_pledgers,
+    })
+}
+
+async fn publish<PredictionMarketEventNostrEventType>(
+    context: &Context,
+    params: &PredictionMarketEventNostrEventType::CreateParameter,
+    dry_run: bool,
+) -> Result<serde_json::Value>
+where
+    PredictionMarketEventNostrEventType: NostrEventUtils,
+{
+    if dry_run {
+        let offline_client = context.offline_client().await?;
+        let sign_result = offline_client
+            .sign::<PredictionMarketEventNostrEventType>(params)
+            .await;
+        // Only a NIP-46 signer has relay connections to close.
+        if let Err(e) = offline_client.disconnect().await {
+            eprintln!("WARNING: failed to disconnect: {e:#}");
+        }
+
+        return Ok(json!(sign_result?));
+    }
+
+    let client = context.client().await?;
+    let nostr_event = client
+        .sign::<PredictionMarketEventNostrEventType>(params)
+        .await?;
+
+    broadcast_or_queue(context, client, nostr_event).await
+}
+
+async fn query_custom<PredictionMarketEventNostrEventType>(
+    context: &Context,
+    filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+    offline: bool,
+    all: bool,
+) -> Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>
+where
+    PredictionMarketEventNostrEventType: NostrEventUtils,
+{
+    match all {
+        true => context.query_all_detailed(filter_fn, offline).await,
+        false => context.query_detailed(filter_fn, offline).await,
+    }
+}
+
+/// Sends the event to relays and saves it to the outbox if fewer relays than required accepted it.
+/// The result has `"queued": true` in that case, the process then exits with [super::QUEUED_EXIT_CODE].
+async fn broadcast_or_queue<State>(
+    context: &Context,
+    client: &Client<State>,
+    nostr_event: nostr_sdk::Event,
+) -> Result<serde_json::Value> {
+    let min_relay_acks = db::Settings::get_min_relay_acks(context).await?;
+
+    let (mut json, acked_relays, error) =
+        match broadcast_with_report(client, nostr_event.clone()).await {
+            Ok(report) => (
+                publish_report_json(&report),
+                report.success.clone(),
+                failed_relays_error(&report),
+            ),
+            Err(e) => (
+                json!({
+                    "event_id": nostr_event.id.to_hex(),
+                    "relays": [],
+                    "error": error_chain(&e),
+                }),
+                HashSet::new(),
+                Some(error_chain(&e)),
+            ),
+        };
+    let queued = acked_relays.len() < min_relay_acks;
+    if queued {
+        db::Outbox::record_attempt(context, &nostr_event, &acked_relays, error).await?;
+    }
+    json["queued"] = json!(queued);
+
+    Ok(json)
+}
+
+/// Same as [Client::broadcast] but also returns the report when no relay accepted the event.
+async fn broadcast_with_report<State>(
+    client: &Client<State>,
+    nostr_event: nostr_sdk::Event,
+) -> std::result::Result<PublishReport, ClientError> {
+    match client.broadcast(nostr_event).await {
+        Err(ClientError::NotPublished(report)) => Ok(*report),
+        result => result,
+    }
+}
+
+/// Error with its source chain, the same as the cli prints errors.
+fn error_chain(e: &ClientError) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(e) = source {
+        message = format!("{message}: {e}");
+        source = e.source();
+    }
+
+    message
+}
+
+fn failed_relays_error(report: &PublishReport) -> Option<String> {
+    if report.failed.is_empty() {
+        return None;
+    }
+
+    let errors: Vec<String> = report
+        .failed
+        .iter()
+        .map(|(url, message)| format!("{url}: {}", message.as_deref().unwrap_or("failed")))
+        .collect();
+
+    Some(errors.join(", "))
+}
+
+fn generated_keys_json(keys: &Keys) -> Result<serde_json::Value> {
+    Ok(json!({
+        "public_key": keys.public_key.to_bech32()?,
+        "warning": "The secret key only exists in the local database. Back it up with `key secret`, it can not be recovered if lost.",
+    }))
+}
+
+fn relay_check_json(relay_check: &RelayCheck) -> serde_json::Value {
+    json!({
+        "url": relay_check.url,
+        "connected": relay_check.connected,
+        "usable": relay_check.is_usable(),
+        "latency_ms": relay_check.latency.map(|latency| latency.as_millis()),
+        "information": relay_check.information,
+        "kinds": relay_check
+            .kinds
+            .iter()
+            .map(|kind_check| json!({
+                "kind": kind_check.kind,
+                "accepted": kind_check.accepted,
+                "returned": kind_check.returned,
+                "message": kind_check.message,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn event_inspect_json(event: &Event) -> Result<serde_json::Value> {
+    let nonce: String = event.nonce.iter().map(|b| format!("{b:02x}")).collect();
+    let validation_error = event
+        .validate(Information::ALL_VARIANT_IDS)
+        .err()
+        .map(|e| e.to_string());
+
+    let (title, description, outcome_titles, expected_payout) = match &event.information {
+        Information::None => (None, None, Vec::new(), None),
+        Information::V1(v1) => {
+            let expected_payout_unix_seconds: i64 = v1.expected_payout_unix_seconds.try_into()?;
+            let Some(utc) = DateTime::from_timestamp(expected_payout_unix_seconds, 0) else {
+                bail!("expected payout date time out of range");
+            };
+            let expected_payout = json!({
+                "unix_seconds": v1.expected_payout_unix_seconds,
+                "utc": utc.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                "local": utc.with_timezone(&Local).to_rfc3339(),
+            });
+
+            (
+                Some(&v1.title),
+                Some(&v1.description),
+                v1.outcome_titles.clone(),
+                Some(expected_payout),
+            )
+        }
+    };
+    let outcomes: Vec<_> = (0..event.outcome_count)
+        .map(|outcome| {
+            json!({
+                "outcome": outcome,
+                "title": outcome_titles.get(usize::from(outcome)),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "event_hash_hex": event.hash_hex()?,
+        "valid": validation_error.is_none(),
+        "validation_error": validation_error,
+        "information_type": event.information.information_variant_id(),
+        "title": title,
+        "description": description,
+        "units_to_payout": event.units_to_payout,
+        "outcomes": outcomes,
+        "expected_payout": expected_payout,
+        "nonce": nonce,
+    }))
+}
+
+#[derive(Deserialize)]
+struct PositionJson {
+    holder: String,
+    outcome: Outcome,
+    units: u64,
+}
+
+impl From<PositionJson> for Position {
+    fn from(position: PositionJson) -> Self {
+        Self {
+            holder: position.holder,
+            outcome: position.outcome,
+            units: position.units,
+        }
+    }
+}
+
+fn settlement_json(
+    event: &Event,
+    consensus: &Consensus,
+    settlement: &Settlement,
+) -> Result<serde_json::Value> {
+    let outcome_titles = match &event.information {
+        Information::None => Vec::new(),
+        Information::V1(v1) => v1.outcome_titles.to_owned(),
+    };
+    let outcomes: Vec<_> = settlement
+        .units_per_outcome
+        .iter()
+        .enumerate()
+        .map(|(outcome, units)| {
+            json!({
+                "outcome": outcome,
+                "title": outcome_titles.get(outcome),
+                "units_per_outcome": units,
+                "payout_per_unit": f64::from(*units) / f64::from(settlement.units_to_payout),
+            })
+        })
+        .collect();
+    let holders: Vec<_> = settlement
+        .holder_payouts
+        .iter()
+        .map(|holder_payout| {
+            json!({
+                "holder": holder_payout.holder,
+                "amount": holder_payout.amount,
+                "remainder": holder_payout.remainder,
+            })
+        })
+        .collect();
+    let attestors = consensus
+        .groups
+        .iter()
+        .find(|group| Some(&group.event_payout) == consensus.winning_payout.as_ref())
+        .map(|group| group.attestors.to_owned())
+        .unwrap_or_default();
+
+    Ok(json!({
+        "units_to_payout": settlement.units_to_payout,
+        "attestors": attestors,
+        "outcomes": outcomes,
+        "holders": holders,
+        "total_amount": settlement.total_amount()?,
+        "total_remainder": settlement.total_remainder()?,
+    }))
+}
+
+fn event_verification_json(
+    nostr_event: &nostr_sdk::Event,
+    event_verification: &EventVerification,
+) -> serde_json::Value {
+    let interpretation = event_verification
+        .interpretation
+        .as_ref()
+        .map(|interpretation| match interpretation {
+            Ok(Interpretation::NewEvent(event)) => json!({"valid": true, "event": event}),
+            Ok(Interpretation::FutureEventPayoutAttestationPledge(pledger, event_hash_hex)) => {
+                json!({
+                    "valid": true,
+                    "future_attesation_pledge_maker": pledger,
+                    "event_hash_hex": event_hash_hex,
+                })
+            }
+            Ok(Interpretation::EventPayoutAttestation(attestor, event_payout)) => {
+                json!({"valid": true, "attestor": attestor, "event_payout": event_payout})
+            }
+            Err(e) => json!({"valid": false, "error": e.to_string()}),
+        });
+    let event_hash = event_verification.event_hash.as_ref().map(|event_hash| {
+        json!({
+            "valid": event_hash.matches(),
+            "hashtag": event_hash.hashtag,
+            "computed": event_hash.computed.as_ref().ok(),
+            "error": event_hash.computed.as_ref().err().map(|e| e.to_string()),
+        })
+    });
+
+    json!({
+        "valid": event_verification.is_valid(),
+        "nostr_event_id": nostr_event.id.to_hex(),
+        "kind": nostr_event.kind.as_u16(),
+        "author": nostr_event.pubkey.to_hex(),
+        "id_valid": event_verification.id_valid,
+        "signature_valid": event_verification.signature_valid,
+        "interpretation": interpretation,
+        "event_hash": event_hash,
+    })
+}
+
+fn outbox_entry_json(entry: &db::OutboxEntry) -> serde_json::Value {
+    json!({
+        "event_id": entry.event.id.to_hex(),
+        "kind": entry.event.kind.as_u16(),
+        "queued_at": entry.queued_at,
+        "attempts": entry.attempts,
+        "next_attempt_at": entry.next_attempt_at,
+        "acked_relays": entry.acked_relays,
+        "last_error": entry.last_error,
+    })
+}
+
+fn publish_report_json(report: &PublishReport) -> serde_json::Value {
+    json!({
+        "event_id": report.event_id.to_hex(),
+        "relays": report.success,
+        "failed_relays": report.failed,
+    })
+}
+
+fn with_rejected_json<PredictionMarketEventNostrEventType: NostrEventUtils>(
+    accepted_json: impl FnOnce(
+        &Vec<(
+            nostr_sdk::Event,
+            PredictionMarketEventNostrEventType::InterpretResult,
+        )>,
+    ) -> serde_json::Value,
+    res: GetDetailedOutput<PredictionMarketEventNostrEventType>,
+    show_rejected: bool,
+    strict: bool,
+) -> Result<serde_json::Value> {
+    if strict {
+        return Ok(accepted_json(&res.strict()?));
+    }
+    if !show_rejected {
+        return Ok(accepted_json(&res.accepted));
+    }
+
+    let accepted_json = accepted_json(&res.accepted);
+    let rejected: Vec<_> = res
+        .rejected
+        .iter()
+        .map(|(nostr_event, e)| json!({"nostr_event": nostr_event, "error": e.to_string()}))
+        .collect();
+
+    Ok(json!({
+        "accepted": accepted_json,
+        "rejected": rejected,
+    }))
+}
+
 const RECOMMENDED_RELAY_LIST: &[&str] = &[
     "wss://btc.klendazu.com",
     "wss://nostr.yael.at",
@@ -1,4 +1,7 @@
-use std::io::{self, Write};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+};
 
 use anyhow::{bail, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
@@ -24,11 +27,7 @@ pub fn information_creator_prompt(
 
             let datetime_string =
                 read_line("Expected Payout Date Time UTC (format: `2023-10-01T12:00:00Z`)");
-            let datetime: DateTime<Utc> = DateTime::from_naive_utc_and_offset(
-                NaiveDateTime::parse_from_str(&datetime_string, "%Y-%m-%dT%H:%M:%SZ")?,
-                Utc,
-            );
-            let expected_payout_unix_seconds: u64 = datetime.timestamp().try_into()?;
+            let expected_payout_unix_seconds = parse_expected_payout(&datetime_string)?;
 
             Information::V1(V1 {
                 title,
@@ -74,6 +73,85 @@ pub fn event_payout_units_per_outcome_creator_prompt(event: &Event) -> Result<Ve
     Ok(units_per_outcome)
 }
 
+/// Parses date time in format `2023-10-01T12:00:00Z` into unix seconds.
+pub fn parse_expected_payout(datetime_string: &str) -> Result<u64> {
+    let datetime: DateTime<Utc> = DateTime::from_naive_utc_and_offset(
+        NaiveDateTime::parse_from_str(datetime_string, "%Y-%m-%dT%H:%M:%SZ")?,
+        Utc,
+    );
+    let expected_payout_unix_seconds: u64 = datetime.timestamp().try_into()?;
+
+    Ok(expected_payout_unix_seconds)
+}
+
+/// Reads whole file, or stdin if path is `-`.
+pub fn read_file_or_stdin(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        return Ok(input);
+    }
+
+    Ok(fs::read_to_string(path)?)
+}
+
+pub const PASSPHRASE_ENV_VAR: &str = "PREDICTION_MARKET_EVENT_CLI_PASSPHRASE";
+
+/// Passphrase from [PASSPHRASE_ENV_VAR] if set, otherwise prompted.
+pub fn passphrase_prompt() -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    Ok(rpassword::prompt_password("Passphrase >> ")?)
+}
+
+pub const MNEMONIC_ENV_VAR: &str = "PREDICTION_MARKET_EVENT_CLI_MNEMONIC";
+
+/// Mnemonic from [MNEMONIC_ENV_VAR] if set, otherwise prompted without echo so it stays out of shell history.
+pub fn mnemonic_prompt() -> Result<String> {
+    if let Ok(mnemonic) = env::var(MNEMONIC_ENV_VAR) {
+        return Ok(mnemonic);
+    }
+
+    Ok(rpassword::prompt_password("Mnemonic >> ")?)
+}
+
+pub const BIP39_PASSPHRASE_ENV_VAR: &str = "PREDICTION_MARKET_EVENT_CLI_BIP39_PASSPHRASE";
+
+/// BIP-39 passphrase from [BIP39_PASSPHRASE_ENV_VAR] if set, otherwise prompted.
+pub fn bip39_passphrase_prompt() -> Result<String> {
+    if let Ok(passphrase) = env::var(BIP39_PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    Ok(rpassword::prompt_password("BIP-39 Passphrase >> ")?)
+}
+
+pub const NEW_PASSPHRASE_ENV_VAR: &str = "PREDICTION_MARKET_EVENT_CLI_NEW_PASSPHRASE";
+
+/// Passphrase from [NEW_PASSPHRASE_ENV_VAR] or [PASSPHRASE_ENV_VAR] if set, otherwise prompted twice.
+pub fn new_passphrase_prompt() -> Result<String> {
+    if let Ok(passphrase) =
+        env::var(NEW_PASSPHRASE_ENV_VAR).or_else(|_| env::var(PASSPHRASE_ENV_VAR))
+    {
+        if passphrase.is_empty() {
+            bail!("passphrase can not be empty")
+        }
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::prompt_password("New Passphrase >> ")?;
+    if passphrase.is_empty() {
+        bail!("passphrase can not be empty")
+    }
+    if rpassword::prompt_password("Repeat New Passphrase >> ")? != passphrase {
+        bail!("passphrases do not match")
+    }
+
+    Ok(passphrase)
+}
+
 fn read_line(prompt: &str) -> String {
     print!("{prompt} >> ");
     io::stdout().flush().unwrap();
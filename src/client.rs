@@ -1,11 +1,29 @@
-use std::{collections::HashSet, marker::PhantomData, time::Duration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
 
-use anyhow::Result;
-use nostr_sdk::{Filter, Keys, Url};
+use futures_util::future::join_all;
+use nostr_sdk::{
+    async_utility,
+    nips::nip65::RelayMetadata,
+    pool::{self, relay::FlagCheck, RelaySendOptions, RelayServiceFlags},
+    EventBuilder, EventId, Filter, NostrSigner, PublicKey, RelayPoolNotification, SubscriptionId,
+    Url,
+};
 use prediction_market_event::nostr_event_types::NostrEventUtils;
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+
+use crate::error::{ClientError, Result};
 
 pub struct Client<State = QueryOnly> {
-    keys: Option<Keys>,
+    signer: Option<NostrSigner>,
     nostr_client: nostr_sdk::Client,
 
     state: PhantomData<State>,
@@ -13,31 +31,147 @@ pub struct Client<State = QueryOnly> {
 pub struct QueryOnly;
 pub struct Signer;
 
+pub struct GetDetailedOutput<PredictionMarketEventNostrEventType: NostrEventUtils> {
+    pub accepted: Vec<(
+        nostr_sdk::Event,
+        PredictionMarketEventNostrEventType::InterpretResult,
+    )>,
+    pub rejected: Vec<(nostr_sdk::Event, prediction_market_event::Error)>,
+}
+
+impl<PredictionMarketEventNostrEventType: NostrEventUtils>
+    GetDetailedOutput<PredictionMarketEventNostrEventType>
+{
+    pub fn from_nostr_events(nostr_events: impl IntoIterator<Item = nostr_sdk::Event>) -> Self {
+        let mut output = Self {
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+        };
+        for nostr_event in nostr_events {
+            match PredictionMarketEventNostrEventType::interpret_nostr_event(&nostr_event) {
+                Ok(interpret_result) => output.accepted.push((nostr_event, interpret_result)),
+                Err(e) => output.rejected.push((nostr_event, e)),
+            }
+        }
+
+        output
+    }
+
+    /// Accepted events, or [ClientError::Interpretation] with the first event that could not be interpreted.
+    pub fn strict(
+        self,
+    ) -> Result<
+        Vec<(
+            nostr_sdk::Event,
+            PredictionMarketEventNostrEventType::InterpretResult,
+        )>,
+    > {
+        match self.rejected.into_iter().next() {
+            Some((_, e)) => Err(ClientError::Interpretation(e)),
+            None => Ok(self.accepted),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PublishReport {
+    pub event_id: EventId,
+    pub success: HashSet<Url>,
+    /// Relays that did not accept the event, with the reason given by the relay if any.
+    pub failed: HashMap<Url, Option<String>>,
+}
+
+/// Stream of [Client::subscribe], the relays are told to close the subscription when it is dropped.
+pub struct Subscription<S> {
+    stream: Pin<Box<S>>,
+    subscription_id: SubscriptionId,
+    nostr_client: nostr_sdk::Client,
+}
+
+impl<S: Stream> Stream for Subscription<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
+impl<S> Drop for Subscription<S> {
+    fn drop(&mut self) {
+        let nostr_client = self.nostr_client.clone();
+        let subscription_id = self.subscription_id.clone();
+        // Closing the subscription sends a message to every relay, which can not be awaited here.
+        let _ = async_utility::thread::spawn(async move {
+            nostr_client.unsubscribe(subscription_id).await;
+        });
+    }
+}
+
+/// Ids of the most recent events, so long running subscriptions do not grow without bound.
+#[derive(Default)]
+struct SeenEventIds {
+    ids: HashSet<EventId>,
+    order: VecDeque<EventId>,
+}
+
+impl SeenEventIds {
+    const CAPACITY: usize = 10_000;
+
+    /// Returns false if the id was already seen.
+    fn insert(&mut self, event_id: EventId) -> bool {
+        if !self.ids.insert(event_id) {
+            return false;
+        }
+        self.order.push_back(event_id);
+        if self.order.len() > Self::CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+
+        true
+    }
+}
+
 impl Client {
-    pub async fn new_initialized_client_query_only(relays: Vec<Url>) -> Result<Client<QueryOnly>> {
+    /// Relays are given like in a NIP-65 relay list, [None] means the relay is used for both reading and writing.
+    /// Only read relays are queried and only write relays are published to.
+    pub async fn new_initialized_client_query_only(
+        relays: Vec<(Url, Option<RelayMetadata>)>,
+    ) -> Result<Client<QueryOnly>> {
         let nostr_client = nostr_sdk::Client::default();
-        for relay in relays {
-            nostr_client.add_relay(relay).await?;
+        for (relay, relay_metadata) in relays {
+            match relay_metadata {
+                None => nostr_client.add_relay(relay).await?,
+                Some(RelayMetadata::Read) => nostr_client.add_read_relay(relay).await?,
+                Some(RelayMetadata::Write) => nostr_client.add_write_relay(relay).await?,
+            };
         }
         nostr_client.connect().await;
 
         Ok(Client {
-            keys: None,
+            signer: None,
             nostr_client,
             state: PhantomData::<QueryOnly>,
         })
     }
+    /// Signer can be local [nostr_sdk::Keys] or a remote signer such as a NIP-46 bunker.
     pub async fn new_initialized_client_signer(
-        relays: Vec<Url>,
-        keys: Keys,
+        relays: Vec<(Url, Option<RelayMetadata>)>,
+        signer: impl Into<NostrSigner>,
     ) -> Result<Client<Signer>> {
         let client_query_only = Self::new_initialized_client_query_only(relays).await?;
 
-        Ok(Client {
-            keys: Some(keys),
-            nostr_client: client_query_only.nostr_client,
+        Ok(client_query_only.with_signer(signer))
+    }
+
+    /// Signer client that shares the relays and connections of this client.
+    pub fn with_signer(&self, signer: impl Into<NostrSigner>) -> Client<Signer> {
+        Client {
+            signer: Some(signer.into()),
+            nostr_client: self.nostr_client.clone(),
             state: PhantomData::<Signer>,
-        })
+        }
     }
 }
 impl<State> Client<State> {
@@ -51,41 +185,255 @@ impl<State> Client<State> {
             PredictionMarketEventNostrEventType::InterpretResult,
         )>,
     >
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let output = self
+            .get_detailed::<PredictionMarketEventNostrEventType>(filter_fn, request_timeout)
+            .await?;
+
+        Ok(output.accepted)
+    }
+
+    /// Same as [Client::get] but also returns the nostr events that could not be interpreted.
+    pub async fn get_detailed<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        request_timeout: Option<Duration>,
+    ) -> Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>
     where
         PredictionMarketEventNostrEventType: NostrEventUtils,
     {
         let filters = filter_fn(PredictionMarketEventNostrEventType::filter());
+        let nostr_event_vec = self.get_nostr_events(filters, request_timeout).await?;
 
+        Ok(GetDetailedOutput::from_nostr_events(nostr_event_vec))
+    }
+
+    /// Gets nostr events without interpreting them.
+    pub async fn get_nostr_events(
+        &self,
+        filters: Vec<Filter>,
+        request_timeout: Option<Duration>,
+    ) -> Result<Vec<nostr_sdk::Event>> {
         let nostr_event_vec = self
             .nostr_client
             .get_events_of(filters, nostr_sdk::EventSource::both(request_timeout))
             .await?;
 
-        let mut interpret_vec = Vec::new();
-        for nostr_event in nostr_event_vec {
-            if let Ok(interpret_result) =
-                PredictionMarketEventNostrEventType::interpret_nostr_event(&nostr_event)
-            {
-                interpret_vec.push((nostr_event, interpret_result));
+        Ok(nostr_event_vec)
+    }
+
+    /// Same as [Client::get_nostr_events] but only queries one relay.
+    pub async fn get_nostr_events_from(
+        &self,
+        url: Url,
+        filters: Vec<Filter>,
+        request_timeout: Option<Duration>,
+    ) -> Result<Vec<nostr_sdk::Event>> {
+        let nostr_event_vec = self
+            .nostr_client
+            .get_events_from([url], filters, request_timeout)
+            .await?;
+
+        Ok(nostr_event_vec)
+    }
+
+    /// Relays that are queried, see [Client::new_initialized_client_query_only].
+    pub(crate) async fn read_relay_urls(&self) -> Vec<Url> {
+        self.nostr_client
+            .pool()
+            .relays_with_flag(RelayServiceFlags::READ, FlagCheck::All)
+            .await
+            .into_keys()
+            .collect()
+    }
+
+    /// Disconnects from all relays, also for clients sharing the connections.
+    /// A NIP-46 signer is shut down too, it has relay connections of its own.
+    pub async fn disconnect(&self) -> Result<()> {
+        self.nostr_client.disconnect().await?;
+        if let Some(NostrSigner::NIP46(nip46_signer)) = &self.signer {
+            nip46_signer
+                .as_ref()
+                .clone()
+                .shutdown()
+                .await
+                .map_err(|e| ClientError::Signing(e.into()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends already signed nostr event to each write relay on its own, so the reason of every relay that refused it is kept.
+    /// Fails with [ClientError::NotPublished] carrying the report if no relay accepted the event.
+    pub async fn broadcast(&self, nostr_event: nostr_sdk::Event) -> Result<PublishReport> {
+        let relays = self
+            .nostr_client
+            .pool()
+            .relays_with_flag(RelayServiceFlags::WRITE, FlagCheck::All)
+            .await;
+        if relays.is_empty() {
+            return Err(ClientError::Relay(nostr_sdk::client::Error::RelayPool(
+                pool::pool::Error::NoRelaysSpecified,
+            )));
+        }
+
+        let send_results = join_all(relays.into_iter().map(|(url, relay)| {
+            let nostr_event = nostr_event.clone();
+            async move {
+                let send_result = relay
+                    .send_event(nostr_event, RelaySendOptions::default())
+                    .await;
+                (url, send_result)
+            }
+        }))
+        .await;
+
+        let mut report = PublishReport {
+            event_id: nostr_event.id,
+            success: HashSet::new(),
+            failed: HashMap::new(),
+        };
+        for (url, send_result) in send_results {
+            match send_result {
+                Ok(_) => {
+                    report.success.insert(url);
+                }
+                Err(pool::relay::Error::EventNotPublished(message)) => {
+                    report.failed.insert(url, Some(message));
+                }
+                Err(e) => {
+                    report.failed.insert(url, Some(e.to_string()));
+                }
             }
         }
+        if report.success.is_empty() {
+            return Err(ClientError::NotPublished(Box::new(report)));
+        }
 
-        Ok(interpret_vec)
+        Ok(report)
+    }
+
+    /// Subscribes to new nostr events matching the filters and streams the ones that can be interpreted.
+    /// Events received from multiple relays are only yielded once.
+    /// Relays resubscribe automatically after reconnecting, the stream ends when the client shuts down.
+    /// Notifications dropped because the stream was not polled fast enough are yielded as [ClientError::Lagged].
+    pub async fn subscribe<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+    ) -> Result<
+        Subscription<
+            impl Stream<
+                Item = Result<(
+                    nostr_sdk::Event,
+                    PredictionMarketEventNostrEventType::InterpretResult,
+                )>,
+            >,
+        >,
+    >
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let filters = filter_fn(PredictionMarketEventNostrEventType::filter());
+
+        let notifications = self.nostr_client.notifications();
+        let subscription_id = self.nostr_client.subscribe(filters, None).await?.val;
+
+        let stream_subscription_id = subscription_id.clone();
+        let mut seen_event_ids = SeenEventIds::default();
+        let stream = BroadcastStream::new(notifications)
+            .take_while(|notification| !matches!(notification, Ok(RelayPoolNotification::Shutdown)))
+            .filter_map(move |notification| {
+                let notification = match notification {
+                    Ok(notification) => notification,
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                        return Some(Err(ClientError::Lagged(skipped)));
+                    }
+                };
+                let RelayPoolNotification::Event {
+                    subscription_id: notification_subscription_id,
+                    event: nostr_event,
+                    ..
+                } = notification
+                else {
+                    return None;
+                };
+                if notification_subscription_id != stream_subscription_id
+                    || !seen_event_ids.insert(nostr_event.id)
+                {
+                    return None;
+                }
+
+                let interpret_result =
+                    PredictionMarketEventNostrEventType::interpret_nostr_event(&nostr_event)
+                        .ok()?;
+
+                Some(Ok((*nostr_event, interpret_result)))
+            });
+
+        Ok(Subscription {
+            stream: Box::pin(stream),
+            subscription_id,
+            nostr_client: self.nostr_client.clone(),
+        })
     }
 }
 
 impl Client<Signer> {
+    /// Signs and broadcasts the event, see [Client::broadcast].
     pub async fn publish<PredictionMarketEventNostrEventType>(
         &self,
         params: &PredictionMarketEventNostrEventType::CreateParameter,
-    ) -> Result<HashSet<Url>>
+    ) -> Result<PublishReport>
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let nostr_event = self
+            .sign::<PredictionMarketEventNostrEventType>(params)
+            .await?;
+
+        self.broadcast(nostr_event).await
+    }
+
+    /// Creates signed nostr event without sending it to any relay.
+    pub async fn sign<PredictionMarketEventNostrEventType>(
+        &self,
+        params: &PredictionMarketEventNostrEventType::CreateParameter,
+    ) -> Result<nostr_sdk::Event>
     where
         PredictionMarketEventNostrEventType: NostrEventUtils,
     {
-        let nostr_event = PredictionMarketEventNostrEventType::create_nostr_event_builder(params)?
-            .to_event(self.keys.as_ref().unwrap())?;
-        let output = self.nostr_client.send_event(nostr_event).await?;
+        let event_builder = PredictionMarketEventNostrEventType::create_nostr_event_builder(params)
+            .map_err(ClientError::Validation)?;
+
+        self.sign_event_builder(event_builder).await
+    }
+
+    pub(crate) async fn sign_event_builder(
+        &self,
+        event_builder: EventBuilder,
+    ) -> Result<nostr_sdk::Event> {
+        let nostr_event = self
+            .signer()
+            .sign_event_builder(event_builder)
+            .await
+            .map_err(ClientError::Signing)?;
+
+        Ok(nostr_event)
+    }
+
+    pub async fn public_key(&self) -> Result<PublicKey> {
+        let public_key = self
+            .signer()
+            .public_key()
+            .await
+            .map_err(ClientError::Signing)?;
+
+        Ok(public_key)
+    }
 
-        Ok(output.success)
+    fn signer(&self) -> &NostrSigner {
+        self.signer.as_ref().unwrap()
     }
 }
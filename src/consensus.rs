@@ -0,0 +1,81 @@
+use std::cmp::Reverse;
+
+use prediction_market_event::{nostr_event_types::NostrPublicKeyHex, EventPayout};
+
+use crate::EventStatus;
+
+/// Minimum number of pledgers (k of n) that must attest to the same payout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quorum {
+    pub k: usize,
+}
+
+/// Pledgers that attested to the same payout.
+pub struct PayoutGroup {
+    pub event_payout: EventPayout,
+    pub attestors: Vec<NostrPublicKeyHex>,
+}
+
+pub struct Consensus {
+    /// Ordered by number of attestors, most first.
+    /// Attestations of conflicting pledgers are not part of any group.
+    pub groups: Vec<PayoutGroup>,
+    /// Pledgers that attested to more than one different payout.
+    pub conflicting_pledgers: Vec<NostrPublicKeyHex>,
+    /// Payout attested by at least [Quorum::k] pledgers and by more pledgers than any other payout.
+    pub winning_payout: Option<EventPayout>,
+}
+
+impl Consensus {
+    pub fn is_reached(&self) -> bool {
+        self.winning_payout.is_some()
+    }
+}
+
+impl EventStatus {
+    /// Only attestations made by pledgers are taken into account.
+    pub fn consensus(&self, quorum: Quorum) -> Consensus {
+        let mut groups: Vec<PayoutGroup> = Vec::new();
+        let mut conflicting_pledgers = Vec::new();
+        for pledger in self.pledgers.iter() {
+            let mut event_payouts: Vec<&EventPayout> = Vec::new();
+            for (attestor, event_payout) in self.attestations.iter() {
+                if attestor == pledger && !event_payouts.contains(&event_payout) {
+                    event_payouts.push(event_payout);
+                }
+            }
+
+            match event_payouts.as_slice() {
+                [] => {}
+                [event_payout] => {
+                    match groups
+                        .iter_mut()
+                        .find(|group| &group.event_payout == *event_payout)
+                    {
+                        Some(group) => group.attestors.push(pledger.to_owned()),
+                        None => groups.push(PayoutGroup {
+                            event_payout: (*event_payout).to_owned(),
+                            attestors: vec![pledger.to_owned()],
+                        }),
+                    }
+                }
+                _ => conflicting_pledgers.push(pledger.to_owned()),
+            }
+        }
+        groups.sort_by_key(|group| Reverse(group.attestors.len()));
+
+        let winning_payout = match groups.as_slice() {
+            [first, second, ..] if first.attestors.len() == second.attestors.len() => None,
+            [first, ..] if first.attestors.len() >= quorum.k.max(1) => {
+                Some(first.event_payout.to_owned())
+            }
+            _ => None,
+        };
+
+        Consensus {
+            groups,
+            conflicting_pledgers,
+            winning_payout,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashSet, time::Duration};
+
+use nostr_sdk::{EventBuilder, EventId, Filter, Kind};
+
+use crate::{
+    client::{PublishReport, Signer},
+    error::Result,
+    Client,
+};
+
+impl Client<Signer> {
+    /// Publishes a NIP-09 deletion request (kind 5) for the events.
+    /// It only has an effect on events signed by the same key.
+    pub async fn delete(
+        &self,
+        event_ids: impl IntoIterator<Item = EventId>,
+        reason: Option<&str>,
+    ) -> Result<PublishReport> {
+        let nostr_event = self.sign_deletion(event_ids, reason).await?;
+
+        self.broadcast(nostr_event).await
+    }
+
+    /// Creates signed deletion request without sending it to any relay.
+    pub async fn sign_deletion(
+        &self,
+        event_ids: impl IntoIterator<Item = EventId>,
+        reason: Option<&str>,
+    ) -> Result<nostr_sdk::Event> {
+        let event_builder = EventBuilder::delete_with_reason(event_ids, reason.unwrap_or_default());
+
+        self.sign_event_builder(event_builder).await
+    }
+}
+
+impl<State> Client<State> {
+    /// Ids of the events whose own author requested their deletion.
+    pub async fn get_deleted_event_ids(
+        &self,
+        nostr_events: &[nostr_sdk::Event],
+        request_timeout: Option<Duration>,
+    ) -> Result<HashSet<EventId>> {
+        if nostr_events.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let deletion_nostr_events = self
+            .get_nostr_events(deletion_filters(nostr_events), request_timeout)
+            .await?;
+
+        Ok(deleted_event_ids(nostr_events, &deletion_nostr_events))
+    }
+}
+
+/// Relays commonly cap the size of filters, so event ids are sent in few filters of this many.
+const EVENT_IDS_PER_FILTER: usize = 100;
+
+/// Deletion requests for the events by any of their authors, one filter per [EVENT_IDS_PER_FILTER] events.
+pub(crate) fn deletion_filters(nostr_events: &[nostr_sdk::Event]) -> Vec<Filter> {
+    nostr_events
+        .chunks(EVENT_IDS_PER_FILTER)
+        .map(|chunk| {
+            Filter::new()
+                .kind(Kind::EventDeletion)
+                .authors(chunk.iter().map(|nostr_event| nostr_event.pubkey))
+                .events(chunk.iter().map(|nostr_event| nostr_event.id))
+        })
+        .collect()
+}
+
+/// Deletion requests by anyone other than the author of an event are ignored.
+pub(crate) fn deleted_event_ids(
+    nostr_events: &[nostr_sdk::Event],
+    deletion_nostr_events: &[nostr_sdk::Event],
+) -> HashSet<EventId> {
+    let deletion_nostr_events: Vec<&nostr_sdk::Event> = deletion_nostr_events
+        .iter()
+        .filter(|deletion_nostr_event| {
+            deletion_nostr_event.kind == Kind::EventDeletion
+                && deletion_nostr_event.verify().is_ok()
+        })
+        .collect();
+
+    nostr_events
+        .iter()
+        .filter(|nostr_event| {
+            deletion_nostr_events.iter().any(|deletion_nostr_event| {
+                deletion_nostr_event.pubkey == nostr_event.pubkey
+                    && deletion_nostr_event
+                        .event_ids()
+                        .any(|event_id| *event_id == nostr_event.id)
+            })
+        })
+        .map(|nostr_event| nostr_event.id)
+        .collect()
+}
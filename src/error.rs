@@ -0,0 +1,53 @@
+use nostr_sdk::pool;
+use thiserror::Error;
+
+use crate::PublishReport;
+
+pub(crate) type Result<T> = std::result::Result<T, ClientError>;
+
+/// The cause of a variant is only available as its source, print errors with their source chain, e.g. with `{:#}` of `anyhow::Error`.
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("relay request failed")]
+    Relay(#[source] nostr_sdk::client::Error),
+
+    #[error("signing failed")]
+    Signing(#[source] nostr_sdk::signer::Error),
+
+    #[error("interpretation failed")]
+    Interpretation(#[source] prediction_market_event::Error),
+
+    #[error("validation failed")]
+    Validation(#[source] prediction_market_event::Error),
+
+    #[error("relay request timed out")]
+    Timeout(#[source] nostr_sdk::client::Error),
+
+    /// Every write relay refused the event, the report has the reason given by each of them.
+    #[error("no relay accepted event {}", .0.event_id)]
+    NotPublished(Box<PublishReport>),
+
+    #[error("subscription fell behind, {0} relay notifications were dropped")]
+    Lagged(u64),
+}
+
+impl From<nostr_sdk::client::Error> for ClientError {
+    fn from(e: nostr_sdk::client::Error) -> Self {
+        let relay_error = match &e {
+            nostr_sdk::client::Error::Relay(relay_error) => Some(relay_error),
+            nostr_sdk::client::Error::RelayPool(pool::pool::Error::Relay(relay_error)) => {
+                Some(relay_error)
+            }
+            _ => None,
+        };
+
+        match relay_error {
+            Some(
+                pool::relay::Error::Timeout
+                | pool::relay::Error::RecvTimeout
+                | pool::relay::Error::WebSocketTimeout,
+            ) => Self::Timeout(e),
+            _ => Self::Relay(e),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashSet, future::Future, time::Duration};
+
+use nostr_sdk::Filter;
+use prediction_market_event::{
+    nostr_event_types::{
+        EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
+        NostrPublicKeyHex,
+    },
+    Event, EventHashHex, EventPayout,
+};
+
+use crate::{
+    client::GetDetailedOutput,
+    deletion::{deleted_event_ids, deletion_filters},
+    error::{ClientError, Result},
+    Client,
+};
+
+/// Everything known about an [Event] on the relays.
+pub struct EventStatus {
+    pub event: Event,
+    /// Public keys that published a [FutureEventPayoutAttestationPledge] for the event.
+    pub pledgers: Vec<NostrPublicKeyHex>,
+    /// [EventPayoutAttestation]s that are valid for the event.
+    pub attestations: Vec<(NostrPublicKeyHex, EventPayout)>,
+    /// [EventPayoutAttestation]s that failed [EventPayout::validate].
+    pub invalid_attestations: Vec<(
+        NostrPublicKeyHex,
+        EventPayout,
+        prediction_market_event::Error,
+    )>,
+    /// Pledgers that have not published a valid attestation yet.
+    pub pending_pledgers: Vec<NostrPublicKeyHex>,
+}
+
+impl EventStatus {
+    /// Builds the status from pledges and attestations that were fetched for the event.
+    /// Pledges and attestations for other events are ignored.
+    pub fn new(
+        event: Event,
+        pledges: impl IntoIterator<Item = (NostrPublicKeyHex, EventHashHex)>,
+        attestations: impl IntoIterator<Item = (NostrPublicKeyHex, EventPayout)>,
+    ) -> Result<Self> {
+        let event_hash_hex = event.hash_hex().map_err(ClientError::Validation)?;
+
+        let mut pledgers = Vec::new();
+        for (pledger, pledge_event_hash_hex) in pledges {
+            if pledge_event_hash_hex == event_hash_hex && !pledgers.contains(&pledger) {
+                pledgers.push(pledger);
+            }
+        }
+
+        let mut valid_attestations = Vec::new();
+        let mut invalid_attestations = Vec::new();
+        for (attestor, event_payout) in attestations {
+            if event_payout.event_hash_hex != event_hash_hex {
+                continue;
+            }
+            match event_payout.validate(&event) {
+                Ok(()) => valid_attestations.push((attestor, event_payout)),
+                Err(e) => invalid_attestations.push((attestor, event_payout, e)),
+            }
+        }
+
+        let pending_pledgers = pledgers
+            .iter()
+            .filter(|pledger| {
+                !valid_attestations
+                    .iter()
+                    .any(|(attestor, _)| attestor == *pledger)
+            })
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            event,
+            pledgers,
+            attestations: valid_attestations,
+            invalid_attestations,
+            pending_pledgers,
+        })
+    }
+}
+
+impl<State> Client<State> {
+    /// Returns [None] if no valid [NewEvent] with the hash was found.
+    /// Pledges whose author requested their deletion are left out.
+    pub async fn event_status(
+        &self,
+        event_hash_hex: &EventHashHex,
+        request_timeout: Option<Duration>,
+    ) -> Result<Option<EventStatus>> {
+        event_status_with(event_hash_hex, |filters| {
+            self.get_nostr_events(filters, request_timeout)
+        })
+        .await
+    }
+}
+
+/// Same as [Client::event_status] with nostr events fetched by `get_nostr_events`.
+pub(crate) async fn event_status_with<E, F, Fut>(
+    event_hash_hex: &EventHashHex,
+    get_nostr_events: F,
+) -> std::result::Result<Option<EventStatus>, E>
+where
+    E: From<ClientError>,
+    F: Fn(Vec<Filter>) -> Fut,
+    Fut: Future<Output = std::result::Result<Vec<nostr_sdk::Event>, E>>,
+{
+    // The filter matches any hashtag but only the first one is the hash of the event.
+    let Some((_, event)) = GetDetailedOutput::<NewEvent>::from_nostr_events(
+        get_nostr_events(vec![NewEvent::filter().hashtag(&event_hash_hex.0)]).await?,
+    )
+    .accepted
+    .into_iter()
+    .find(|(_, event)| {
+        event
+            .hash_hex()
+            .is_ok_and(|hash_hex| hash_hex == *event_hash_hex)
+    }) else {
+        return Ok(None);
+    };
+    let pledges = GetDetailedOutput::<FutureEventPayoutAttestationPledge>::from_nostr_events(
+        get_nostr_events(vec![
+            FutureEventPayoutAttestationPledge::filter().hashtag(&event_hash_hex.0)
+        ])
+        .await?,
+    )
+    .accepted;
+    let pledge_nostr_events: Vec<nostr_sdk::Event> = pledges
+        .iter()
+        .map(|(nostr_event, _)| nostr_event.to_owned())
+        .collect();
+    let deleted_pledge_ids = match pledge_nostr_events.is_empty() {
+        true => HashSet::new(),
+        false => {
+            let deletion_nostr_events =
+                get_nostr_events(deletion_filters(&pledge_nostr_events)).await?;
+            deleted_event_ids(&pledge_nostr_events, &deletion_nostr_events)
+        }
+    };
+    let attestations = GetDetailedOutput::<EventPayoutAttestation>::from_nostr_events(
+        get_nostr_events(vec![
+            EventPayoutAttestation::filter().hashtag(&event_hash_hex.0)
+        ])
+        .await?,
+    )
+    .accepted;
+
+    let event_status = EventStatus::new(
+        event,
+        pledges
+            .into_iter()
+            .filter(|(nostr_event, _)| !deleted_pledge_ids.contains(&nostr_event.id))
+            .map(|(_, pledge)| pledge),
+        attestations.into_iter().map(|(_, attestation)| attestation),
+    )?;
+
+    Ok(Some(event_status))
+}
@@ -1,8 +1,25 @@
 mod client;
+mod consensus;
+mod deletion;
+mod error;
+mod event_status;
+mod pagination;
+mod pending_attestations;
+mod relay_check;
+mod relay_list;
+mod settlement;
+mod verification;
 
-pub use client::Client;
+pub use client::{Client, GetDetailedOutput, PublishReport, Subscription};
+pub use consensus::{Consensus, PayoutGroup, Quorum};
+pub use error::ClientError;
+pub use event_status::EventStatus;
 pub use nostr_sdk;
+pub use pending_attestations::PendingAttestations;
 pub use prediction_market_event;
+pub use relay_check::{KindCheck, RelayCheck};
+pub use settlement::{HolderPayout, Position, Settlement};
+pub use verification::{EventHashCheck, EventVerification, Interpretation};
 
 #[cfg(feature = "cli")]
 pub mod cli;
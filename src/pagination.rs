@@ -0,0 +1,141 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use futures_util::{stream, Stream, TryStreamExt};
+use nostr_sdk::{EventId, Filter, Timestamp, Url};
+use prediction_market_event::nostr_event_types::NostrEventUtils;
+
+use crate::{client::GetDetailedOutput, error::Result, Client};
+
+impl<State> Client<State> {
+    /// Same as [Client::get_detailed] but not truncated by relay side result limits.
+    /// See [Client::get_nostr_event_pages].
+    pub async fn get_all_paginated<PredictionMarketEventNostrEventType>(
+        &self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        page_size: usize,
+        request_timeout: Option<Duration>,
+    ) -> Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils,
+    {
+        let filters = filter_fn(PredictionMarketEventNostrEventType::filter());
+        let nostr_event_pages: Vec<Vec<nostr_sdk::Event>> = self
+            .get_nostr_event_pages(filters, page_size, request_timeout)
+            .try_collect()
+            .await?;
+
+        Ok(GetDetailedOutput::from_nostr_events(
+            nostr_event_pages.into_iter().flatten(),
+        ))
+    }
+
+    /// Streams the pages of [Client::get_all_paginated] as they are received.
+    pub fn get_paginated<'a, PredictionMarketEventNostrEventType>(
+        &'a self,
+        filter_fn: impl FnOnce(Filter) -> Vec<Filter>,
+        page_size: usize,
+        request_timeout: Option<Duration>,
+    ) -> impl Stream<Item = Result<GetDetailedOutput<PredictionMarketEventNostrEventType>>> + 'a
+    where
+        PredictionMarketEventNostrEventType: NostrEventUtils + 'a,
+    {
+        let filters = filter_fn(PredictionMarketEventNostrEventType::filter());
+
+        self.get_nostr_event_pages(filters, page_size, request_timeout)
+            .map_ok(GetDetailedOutput::from_nostr_events)
+    }
+
+    /// Requests every filter from every read relay on its own, walking back page by page.
+    /// Each filter and relay pair keeps its own `until`, so a dense relay or filter is not skipped because of a sparse one.
+    /// Filter limits are replaced by the page size and a pair is done once it returns fewer events than the page size.
+    /// Each page only contains events that were not in an earlier page.
+    /// Events sharing one timestamp beyond the page size can not be requested with `until` and are skipped.
+    /// The stream ends with an error if a relay can not be queried.
+    pub fn get_nostr_event_pages(
+        &self,
+        filters: Vec<Filter>,
+        page_size: usize,
+        request_timeout: Option<Duration>,
+    ) -> impl Stream<Item = Result<Vec<nostr_sdk::Event>>> + '_ {
+        let initial_state = Some((None::<VecDeque<Window>>, HashSet::<EventId>::new()));
+
+        stream::unfold(initial_state, move |state| {
+            let filters = filters.clone();
+            async move {
+                let (windows, mut seen_event_ids) = state?;
+                let mut windows = match windows {
+                    Some(windows) => windows,
+                    None => self
+                        .read_relay_urls()
+                        .await
+                        .into_iter()
+                        .flat_map(|url| {
+                            filters.iter().map(move |filter| Window {
+                                url: url.clone(),
+                                filter: filter.to_owned(),
+                                until: None,
+                            })
+                        })
+                        .collect(),
+                };
+
+                while let Some(window) = windows.pop_front() {
+                    let mut window_filter = window.filter.clone().limit(page_size);
+                    if let Some(until) = window.until {
+                        window_filter = window_filter.until(until);
+                    }
+                    let nostr_events = match self
+                        .get_nostr_events_from(
+                            window.url.clone(),
+                            vec![window_filter],
+                            request_timeout,
+                        )
+                        .await
+                    {
+                        Ok(nostr_events) => nostr_events,
+                        Err(e) => return Some((Err(e), None)),
+                    };
+
+                    let oldest = nostr_events
+                        .iter()
+                        .map(|nostr_event| nostr_event.created_at)
+                        .min();
+                    if let (true, Some(oldest)) = (nostr_events.len() >= page_size, oldest) {
+                        // Events with the oldest timestamp are requested again and filtered out as already seen,
+                        // unless the whole page had one timestamp, then the window has to move past it.
+                        let until = match window.until {
+                            Some(until) if oldest >= until => {
+                                Timestamp::from(oldest.as_u64().saturating_sub(1))
+                            }
+                            _ => oldest,
+                        };
+                        windows.push_back(Window {
+                            until: Some(until),
+                            ..window
+                        });
+                    }
+
+                    let new_nostr_events: Vec<nostr_sdk::Event> = nostr_events
+                        .into_iter()
+                        .filter(|nostr_event| seen_event_ids.insert(nostr_event.id))
+                        .collect();
+                    if !new_nostr_events.is_empty() {
+                        return Some((Ok(new_nostr_events), Some((Some(windows), seen_event_ids))));
+                    }
+                }
+
+                None
+            }
+        })
+    }
+}
+
+/// Part of the history of one filter on one relay that was not requested yet.
+struct Window {
+    url: Url,
+    filter: Filter,
+    until: Option<Timestamp>,
+}
@@ -0,0 +1,165 @@
+use std::{cmp::Reverse, collections::HashSet, future::Future, time::Duration};
+
+use nostr_sdk::{Filter, PublicKey};
+use prediction_market_event::{
+    nostr_event_types::{
+        EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
+    },
+    Event, EventHashHex,
+};
+
+use crate::{
+    client::GetDetailedOutput,
+    deletion::{deleted_event_ids, deletion_filters},
+    error::{ClientError, Result},
+    Client,
+};
+
+/// Relays commonly cap the number of filters per request, so hashtags are sent in few filters of this many.
+const HASHTAGS_PER_FILTER: usize = 100;
+
+/// Result of [Client::pending_attestations].
+pub struct PendingAttestations {
+    /// Events pledged to but not attested yet, newest first.
+    pub events: Vec<(nostr_sdk::Event, Event)>,
+    /// Relay responses that were ignored, e.g. notes by other authors than the one asked for.
+    pub warnings: Vec<String>,
+}
+
+impl<State> Client<State> {
+    /// Events the public key pledged to attest to and has not attested to yet.
+    /// Pledges deleted by their author are left out.
+    pub async fn pending_attestations(
+        &self,
+        public_key: PublicKey,
+        request_timeout: Option<Duration>,
+    ) -> Result<PendingAttestations> {
+        pending_attestations_with(public_key, |filters| {
+            self.get_nostr_events(filters, request_timeout)
+        })
+        .await
+    }
+}
+
+/// Same as [Client::pending_attestations] with nostr events fetched by `get_nostr_events`.
+pub(crate) async fn pending_attestations_with<E, F, Fut>(
+    public_key: PublicKey,
+    get_nostr_events: F,
+) -> std::result::Result<PendingAttestations, E>
+where
+    E: From<ClientError>,
+    F: Fn(Vec<Filter>) -> Fut,
+    Fut: Future<Output = std::result::Result<Vec<nostr_sdk::Event>, E>>,
+{
+    let mut warnings = Vec::new();
+    let author = public_key.to_hex();
+
+    let pledges = GetDetailedOutput::<FutureEventPayoutAttestationPledge>::from_nostr_events(
+        get_nostr_events(vec![
+            FutureEventPayoutAttestationPledge::filter().author(public_key)
+        ])
+        .await?,
+    )
+    .accepted;
+    let pledges: Vec<_> = pledges
+        .into_iter()
+        .filter(|(nostr_event, (pledger, _))| {
+            let is_author = pledger.0 == author;
+            if !is_author {
+                warnings.push(unexpected_author_warning(nostr_event));
+            }
+            is_author
+        })
+        .collect();
+    let pledge_nostr_events: Vec<nostr_sdk::Event> = pledges
+        .iter()
+        .map(|(nostr_event, _)| nostr_event.to_owned())
+        .collect();
+    let deleted_pledge_ids = match pledge_nostr_events.is_empty() {
+        true => HashSet::new(),
+        false => {
+            let deletion_nostr_events =
+                get_nostr_events(deletion_filters(&pledge_nostr_events)).await?;
+            deleted_event_ids(&pledge_nostr_events, &deletion_nostr_events)
+        }
+    };
+    let pledged: HashSet<EventHashHex> = pledges
+        .into_iter()
+        .filter(|(nostr_event, _)| !deleted_pledge_ids.contains(&nostr_event.id))
+        .map(|(_, (_, event_hash_hex))| event_hash_hex)
+        .collect();
+    if pledged.is_empty() {
+        return Ok(PendingAttestations {
+            events: Vec::new(),
+            warnings,
+        });
+    }
+
+    let attestation_filters = hashtag_filters(
+        EventPayoutAttestation::filter().author(public_key),
+        &pledged,
+    );
+    let attestations = GetDetailedOutput::<EventPayoutAttestation>::from_nostr_events(
+        get_nostr_events(attestation_filters).await?,
+    )
+    .accepted;
+    let mut attested = HashSet::new();
+    for (nostr_event, (attestor, event_payout)) in attestations {
+        if attestor.0 != author {
+            warnings.push(unexpected_author_warning(&nostr_event));
+            continue;
+        }
+        attested.insert(event_payout.event_hash_hex);
+    }
+
+    let pending: HashSet<EventHashHex> = pledged.difference(&attested).cloned().collect();
+    if pending.is_empty() {
+        return Ok(PendingAttestations {
+            events: Vec::new(),
+            warnings,
+        });
+    }
+
+    let new_events = GetDetailedOutput::<NewEvent>::from_nostr_events(
+        get_nostr_events(hashtag_filters(NewEvent::filter(), &pending)).await?,
+    )
+    .accepted;
+    let mut events = Vec::new();
+    for (nostr_event, event) in new_events {
+        let event_hash_hex = event.hash_hex().map_err(ClientError::Validation)?;
+        if !pending.contains(&event_hash_hex) {
+            warnings.push(format!(
+                "ignored nostr event {} for event {event_hash_hex} that was not asked for",
+                nostr_event.id
+            ));
+            continue;
+        }
+        events.push((nostr_event, event));
+    }
+    events.sort_by_key(|(nostr_event, _)| Reverse(nostr_event.created_at));
+
+    Ok(PendingAttestations { events, warnings })
+}
+
+/// One filter per [HASHTAGS_PER_FILTER] event hashes.
+fn hashtag_filters(filter: Filter, event_hash_hexes: &HashSet<EventHashHex>) -> Vec<Filter> {
+    let event_hash_hexes: Vec<&EventHashHex> = event_hash_hexes.iter().collect();
+
+    event_hash_hexes
+        .chunks(HASHTAGS_PER_FILTER)
+        .map(|chunk| {
+            filter.clone().hashtags(
+                chunk
+                    .iter()
+                    .map(|event_hash_hex| event_hash_hex.0.to_owned()),
+            )
+        })
+        .collect()
+}
+
+fn unexpected_author_warning(nostr_event: &nostr_sdk::Event) -> String {
+    format!(
+        "ignored nostr event {} by unexpected author {}",
+        nostr_event.id, nostr_event.pubkey
+    )
+}
@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+use nostr_sdk::{
+    async_utility::time,
+    nips::nip11::RelayInformationDocument,
+    pool::{relay, FilterOptions, Relay, RelayOptions, RelaySendOptions},
+    EventBuilder, Filter, Keys, Kind, Tag, Timestamp, Url,
+};
+use prediction_market_event::nostr_event_types::{
+    EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
+};
+
+use crate::Client;
+
+/// How long probe events should be kept by relays that support NIP-40 expiration.
+const PROBE_EVENT_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Result of [Client::check_relay].
+pub struct RelayCheck {
+    pub url: Url,
+    pub connected: bool,
+    /// Time from sending a request until the relay signalled end of stored events.
+    pub latency: Option<Duration>,
+    /// NIP-11 relay information document.
+    pub information: Option<RelayInformationDocument>,
+    /// One entry per prediction market event kind, empty if the relay could not be connected to.
+    pub kinds: Vec<KindCheck>,
+}
+
+pub struct KindCheck {
+    pub kind: u16,
+    pub accepted: bool,
+    /// Probe event was returned when requested by id.
+    pub returned: bool,
+    /// Reason given by the relay for not accepting the probe event.
+    pub message: Option<String>,
+}
+
+impl RelayCheck {
+    pub fn is_usable(&self) -> bool {
+        self.connected && self.kinds.iter().all(|kind| kind.accepted && kind.returned)
+    }
+}
+
+impl Client {
+    /// Connects to the relay on its own and probes it with an event of each prediction market event kind.
+    /// Probe events are signed by a throwaway key, have empty content and expire shortly (NIP-40).
+    pub async fn check_relay(url: Url, timeout: Duration) -> RelayCheck {
+        let information = time::timeout(
+            Some(timeout),
+            RelayInformationDocument::get(url.clone(), None),
+        )
+        .await
+        .and_then(|document| document.ok());
+
+        let relay = Relay::with_opts(url.clone(), RelayOptions::default().reconnect(false));
+        relay.connect(Some(timeout)).await;
+        if !relay.is_connected().await {
+            return RelayCheck {
+                url,
+                connected: false,
+                latency: None,
+                information,
+                kinds: Vec::new(),
+            };
+        }
+
+        let start = Instant::now();
+        let latency = relay
+            .get_events_of(
+                vec![Filter::new().kind(Kind::from(NewEvent::KIND_U16)).limit(1)],
+                timeout,
+                FilterOptions::ExitOnEOSE,
+            )
+            .await
+            .ok()
+            .map(|_| start.elapsed());
+
+        let keys = Keys::generate();
+        let mut kinds = Vec::new();
+        for kind in [
+            NewEvent::KIND_U16,
+            FutureEventPayoutAttestationPledge::KIND_U16,
+            EventPayoutAttestation::KIND_U16,
+        ] {
+            kinds.push(probe_kind(&relay, &keys, kind, timeout).await);
+        }
+        let _ = relay.disconnect().await;
+
+        RelayCheck {
+            url,
+            connected: true,
+            latency,
+            information,
+            kinds,
+        }
+    }
+}
+
+async fn probe_kind(relay: &Relay, keys: &Keys, kind: u16, timeout: Duration) -> KindCheck {
+    let expiration = Timestamp::now() + PROBE_EVENT_LIFETIME;
+    let probe_event = match EventBuilder::new(Kind::from(kind), "", [Tag::expiration(expiration)])
+        .to_event(keys)
+    {
+        Ok(probe_event) => probe_event,
+        Err(e) => {
+            return KindCheck {
+                kind,
+                accepted: false,
+                returned: false,
+                message: Some(e.to_string()),
+            }
+        }
+    };
+    let probe_event_id = probe_event.id;
+
+    let send_options = RelaySendOptions::new().timeout(Some(timeout));
+    let (accepted, message) = match relay.send_event(probe_event, send_options).await {
+        Ok(_) => (true, None),
+        Err(relay::Error::EventNotPublished(message)) => (false, Some(message)),
+        Err(e) => (false, Some(e.to_string())),
+    };
+
+    let returned = match accepted {
+        true => relay
+            .get_events_of(
+                vec![Filter::new().id(probe_event_id)],
+                timeout,
+                FilterOptions::ExitOnEOSE,
+            )
+            .await
+            .is_ok_and(|nostr_events| {
+                nostr_events
+                    .iter()
+                    .any(|nostr_event| nostr_event.id == probe_event_id)
+            }),
+        false => false,
+    };
+
+    KindCheck {
+        kind,
+        accepted,
+        returned,
+        message,
+    }
+}
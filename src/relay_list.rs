@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use nostr_sdk::{
+    nips::nip65::{self, RelayMetadata},
+    EventBuilder, Filter, Kind, PublicKey, Url,
+};
+
+use crate::{
+    client::{PublishReport, Signer},
+    error::Result,
+    Client,
+};
+
+impl<State> Client<State> {
+    /// Relays from the newest NIP-65 relay list (kind 10002) of the public key.
+    /// Returns [None] if the public key has not published a relay list.
+    pub async fn get_relay_list(
+        &self,
+        public_key: PublicKey,
+        request_timeout: Option<Duration>,
+    ) -> Result<Option<Vec<(Url, Option<RelayMetadata>)>>> {
+        let filter = Filter::new()
+            .kind(Kind::RelayList)
+            .author(public_key)
+            .limit(1);
+        let Some(nostr_event) = self
+            .get_nostr_events(vec![filter], request_timeout)
+            .await?
+            .into_iter()
+            .max_by_key(|nostr_event| nostr_event.created_at)
+        else {
+            return Ok(None);
+        };
+
+        let relay_list = nip65::extract_relay_list(&nostr_event)
+            .map(|(url, relay_metadata)| (url.to_owned(), relay_metadata.to_owned()))
+            .collect();
+
+        Ok(Some(relay_list))
+    }
+}
+
+impl Client<Signer> {
+    /// Publishes the relays as NIP-65 relay list (kind 10002), replacing the previously published one.
+    pub async fn publish_relay_list(
+        &self,
+        relays: Vec<(Url, Option<RelayMetadata>)>,
+    ) -> Result<PublishReport> {
+        let nostr_event = self
+            .sign_event_builder(EventBuilder::relay_list(relays))
+            .await?;
+
+        self.broadcast(nostr_event).await
+    }
+}
@@ -0,0 +1,118 @@
+use prediction_market_event::{information::Information, Event, EventPayout, Outcome, PayoutUnit};
+
+use crate::error::{ClientError, Result};
+
+/// Units of one outcome of an [Event] held by a holder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub holder: String,
+    pub outcome: Outcome,
+    pub units: u64,
+}
+
+/// Payout of a settled [Event]. Payouts are fractions with [Settlement::units_to_payout] as denominator.
+pub struct Settlement {
+    pub units_to_payout: PayoutUnit,
+    /// Payout of one unit of each outcome, in 1/[Settlement::units_to_payout].
+    pub units_per_outcome: Vec<PayoutUnit>,
+    /// One entry per holder, in order of their first position.
+    pub holder_payouts: Vec<HolderPayout>,
+}
+
+pub struct HolderPayout {
+    pub holder: String,
+    /// Payout of all positions of the holder, rounded down.
+    pub amount: u64,
+    /// Part of the payout lost by rounding down, in 1/[Settlement::units_to_payout].
+    pub remainder: u64,
+}
+
+impl Settlement {
+    /// Settles positions with the payout that won consensus for the event.
+    /// Fails with [ClientError::Validation] if the event or the payout is invalid, events from relays are not validated.
+    pub fn new(event: &Event, event_payout: &EventPayout, positions: &[Position]) -> Result<Self> {
+        event
+            .validate(Information::ALL_VARIANT_IDS)
+            .map_err(ClientError::Validation)?;
+        event_payout
+            .validate(event)
+            .map_err(ClientError::Validation)?;
+
+        let mut holder_payouts: Vec<(String, u128)> = Vec::new();
+        for position in positions {
+            let Some(units_per_outcome) = event_payout
+                .units_per_outcome
+                .get(usize::from(position.outcome))
+            else {
+                return Err(ClientError::Validation(
+                    prediction_market_event::Error::Validation(format!(
+                        "position of {} is in outcome {} but event only has {} outcomes",
+                        position.holder, position.outcome, event.outcome_count
+                    )),
+                ));
+            };
+            let payout = u128::from(position.units) * u128::from(*units_per_outcome);
+
+            match holder_payouts
+                .iter_mut()
+                .find(|(holder, _)| *holder == position.holder)
+            {
+                Some((_, holder_payout)) => *holder_payout += payout,
+                None => holder_payouts.push((position.holder.to_owned(), payout)),
+            }
+        }
+
+        let units_to_payout = u128::from(event.units_to_payout);
+        let holder_payouts = holder_payouts
+            .into_iter()
+            .map(|(holder, payout)| {
+                Ok(HolderPayout {
+                    holder,
+                    amount: (payout / units_to_payout).try_into().map_err(|_| {
+                        ClientError::Validation(prediction_market_event::Error::Validation(
+                            "holder payout does not fit into 64 bits".to_owned(),
+                        ))
+                    })?,
+                    remainder: (payout % units_to_payout)
+                        .try_into()
+                        .expect("remainder is less than units to payout"),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            units_to_payout: event.units_to_payout,
+            units_per_outcome: event_payout.units_per_outcome.to_owned(),
+            holder_payouts,
+        })
+    }
+
+    /// Fails with [ClientError::Validation] if the sum does not fit into 64 bits.
+    pub fn total_amount(&self) -> Result<u64> {
+        checked_sum(
+            self.holder_payouts
+                .iter()
+                .map(|holder_payout| holder_payout.amount),
+            "total amount",
+        )
+    }
+
+    /// Sum of the remainders of all holders, in 1/[Settlement::units_to_payout].
+    /// Fails with [ClientError::Validation] if the sum does not fit into 64 bits.
+    pub fn total_remainder(&self) -> Result<u64> {
+        checked_sum(
+            self.holder_payouts
+                .iter()
+                .map(|holder_payout| holder_payout.remainder),
+            "total remainder",
+        )
+    }
+}
+
+fn checked_sum(mut values: impl Iterator<Item = u64>, name: &str) -> Result<u64> {
+    values.try_fold(0u64, u64::checked_add).ok_or_else(|| {
+        ClientError::Validation(prediction_market_event::Error::Validation(format!(
+            "{name} does not fit into 64 bits"
+        )))
+    })
+}
@@ -0,0 +1,91 @@
+use prediction_market_event::{
+    nostr_event_types::{
+        EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
+        NostrPublicKeyHex,
+    },
+    Event, EventHashHex, EventPayout,
+};
+
+/// Result of [EventVerification::new]. Every check is done on its own so the report shows everything that is wrong.
+pub struct EventVerification {
+    /// Event id matches the id computed from the event fields.
+    pub id_valid: bool,
+    /// Schnorr signature of the event id by the author is valid.
+    pub signature_valid: bool,
+    /// Result of `interpret_nostr_event` for the type matching the kind.
+    /// None if the kind is not a prediction market event kind.
+    pub interpretation: Option<Result<Interpretation, prediction_market_event::Error>>,
+    /// Only for [NewEvent].
+    pub event_hash: Option<EventHashCheck>,
+}
+
+pub enum Interpretation {
+    NewEvent(Event),
+    FutureEventPayoutAttestationPledge(NostrPublicKeyHex, EventHashHex),
+    EventPayoutAttestation(NostrPublicKeyHex, EventPayout),
+}
+
+pub struct EventHashCheck {
+    /// First hashtag of the nostr event.
+    pub hashtag: Option<String>,
+    /// [Event::hash_hex] recomputed from the event in the content.
+    pub computed: Result<EventHashHex, prediction_market_event::Error>,
+}
+
+impl EventVerification {
+    /// Verifies a nostr event without contacting relays.
+    pub fn new(nostr_event: &nostr_sdk::Event) -> Self {
+        let kind = nostr_event.kind.as_u16();
+        let interpretation = if kind == NewEvent::KIND_U16 {
+            Some(NewEvent::interpret_nostr_event(nostr_event).map(Interpretation::NewEvent))
+        } else if kind == FutureEventPayoutAttestationPledge::KIND_U16 {
+            Some(
+                FutureEventPayoutAttestationPledge::interpret_nostr_event(nostr_event).map(
+                    |(pledger, event_hash_hex)| {
+                        Interpretation::FutureEventPayoutAttestationPledge(pledger, event_hash_hex)
+                    },
+                ),
+            )
+        } else if kind == EventPayoutAttestation::KIND_U16 {
+            Some(
+                EventPayoutAttestation::interpret_nostr_event(nostr_event).map(
+                    |(attestor, event_payout)| {
+                        Interpretation::EventPayoutAttestation(attestor, event_payout)
+                    },
+                ),
+            )
+        } else {
+            None
+        };
+
+        let event_hash = (kind == NewEvent::KIND_U16).then(|| EventHashCheck {
+            hashtag: nostr_event.hashtags().next().map(|s| s.to_owned()),
+            computed: Event::try_from_json_str(&nostr_event.content)
+                .and_then(|event| event.hash_hex()),
+        });
+
+        Self {
+            id_valid: nostr_event.verify_id(),
+            signature_valid: nostr_event.verify_signature(),
+            interpretation,
+            event_hash,
+        }
+    }
+
+    /// All checks passed and the kind is a prediction market event kind.
+    pub fn is_valid(&self) -> bool {
+        self.id_valid
+            && self.signature_valid
+            && matches!(self.interpretation, Some(Ok(_)))
+            && self.event_hash.as_ref().is_none_or(EventHashCheck::matches)
+    }
+}
+
+impl EventHashCheck {
+    pub fn matches(&self) -> bool {
+        match (&self.hashtag, &self.computed) {
+            (Some(hashtag), Ok(computed)) => *hashtag == computed.0,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli, MockRelay, TempFile};
+use nostr_sdk::{Event, Filter, JsonUtil};
+use prediction_market_event_nostr_client::{cli::Context, Client};
+
+const TIMEOUT: Option<std::time::Duration> = Some(std::time::Duration::from_secs(5));
+
+async fn context_with_relay(relay: &MockRelay) -> Context {
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+    run_cli(&context, &["relay", "add", relay.url.as_str()])
+        .await
+        .unwrap();
+
+    context
+}
+
+async fn relay_events(relay: &MockRelay) -> Vec<Event> {
+    let client = Client::new_initialized_client_query_only(vec![(relay.url.clone(), None)])
+        .await
+        .unwrap();
+
+    client
+        .get_nostr_events(vec![Filter::new()], TIMEOUT)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn dry_run_new_event_can_be_broadcast_later() {
+    let relay = MockRelay::run().await;
+    let context = context_with_relay(&relay).await;
+
+    let dry_run = run_cli(
+        &context,
+        &["publish", "--dry-run", "new-event", "2", "100", "none"],
+    )
+    .await
+    .unwrap();
+    assert!(dry_run["hash_hex"].is_string());
+    let nostr_event_json = &dry_run["event"];
+    let mut keys: Vec<&str> = nostr_event_json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "content",
+            "created_at",
+            "id",
+            "kind",
+            "pubkey",
+            "sig",
+            "tags"
+        ]
+    );
+    assert!(relay_events(&relay).await.is_empty());
+
+    let file = TempFile::new("dry_run.json", &dry_run.to_string());
+    let broadcast = run_cli(&context, &["broadcast", file.path_str()])
+        .await
+        .unwrap();
+
+    assert_eq!(broadcast["event_id"], nostr_event_json["id"]);
+    assert_eq!(broadcast["queued"], false);
+    let relay_events = relay_events(&relay).await;
+    assert_eq!(relay_events.len(), 1);
+    assert_eq!(
+        relay_events[0].as_json(),
+        Event::from_json(nostr_event_json.to_string())
+            .unwrap()
+            .as_json()
+    );
+}
+
+#[tokio::test]
+async fn tampered_events_are_not_broadcast() {
+    let relay = MockRelay::run().await;
+    let context = context_with_relay(&relay).await;
+    let dry_run = run_cli(
+        &context,
+        &["publish", "--dry-run", "new-event", "2", "100", "none"],
+    )
+    .await
+    .unwrap();
+    let nostr_event_json = &dry_run["event"];
+
+    let mut tampered_content = nostr_event_json.clone();
+    let created_at = nostr_event_json["created_at"].as_u64().unwrap();
+    tampered_content["created_at"] = serde_json::json!(created_at + 1);
+    let mut tampered_signature = nostr_event_json.clone();
+    let signature = nostr_event_json["sig"].as_str().unwrap();
+    let flipped = if signature.starts_with('0') { "1" } else { "0" };
+    tampered_signature["sig"] = serde_json::json!(format!("{flipped}{}", &signature[1..]));
+
+    for (name, tampered, error) in [
+        ("content", tampered_content, "does not match its content"),
+        ("signature", tampered_signature, "invalid signature"),
+    ] {
+        let file = TempFile::new(&format!("{name}.json"), &tampered.to_string());
+        let result = run_cli(&context, &["broadcast", file.path_str()]).await;
+
+        let e = result.unwrap_err();
+        assert!(e.to_string().contains(error), "{name}: {e}");
+    }
+    assert!(relay_events(&relay).await.is_empty());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli};
+use prediction_market_event_nostr_client::cli::stdin_prompts::{
+    NEW_PASSPHRASE_ENV_VAR, PASSPHRASE_ENV_VAR,
+};
+
+/// In a test binary of its own, the new passphrase env var would change the passphrase of `key encrypt` in other tests.
+#[tokio::test]
+async fn change_passphrase_takes_the_new_passphrase_from_the_env() {
+    std::env::set_var(PASSPHRASE_ENV_VAR, "old passphrase");
+    let context = cli_context().await;
+    let generated = run_cli(&context, &["key", "generate"]).await.unwrap();
+    run_cli(&context, &["key", "encrypt"]).await.unwrap();
+
+    std::env::set_var(NEW_PASSPHRASE_ENV_VAR, "new passphrase");
+    run_cli(&context, &["key", "change-passphrase"])
+        .await
+        .unwrap();
+    std::env::remove_var(NEW_PASSPHRASE_ENV_VAR);
+
+    assert!(run_cli(&context, &["key", "decrypt"]).await.is_err());
+    std::env::set_var(PASSPHRASE_ENV_VAR, "new passphrase");
+    run_cli(&context, &["key", "decrypt"]).await.unwrap();
+    assert_eq!(
+        run_cli(&context, &["key", "public"]).await.unwrap(),
+        generated["public_key"]
+    );
+}
@@ -0,0 +1,313 @@
+// Shared by several test crates, not every crate uses every helper.
+#![allow(dead_code)]
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures_util::{SinkExt, StreamExt};
+use nostr_sdk::{ClientMessage, Event, Filter, JsonUtil, Kind, RelayMessage, SubscriptionId, Url};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+pub const MOCK_RELAY_NAME: &str = "mock relay";
+
+/// Minimal in-process relay: stores every event, answers REQ with stored events (newest first, limited) and streams new ones.
+/// Plain HTTP requests get a NIP-11 relay information document.
+pub struct MockRelay {
+    pub url: Url,
+    open_subscriptions: Arc<AtomicUsize>,
+}
+
+#[derive(Clone)]
+struct State {
+    events: Arc<Mutex<Vec<Event>>>,
+    new_events: broadcast::Sender<Event>,
+    rejected_kinds: Vec<Kind>,
+    ignore_authors: bool,
+    open_subscriptions: Arc<AtomicUsize>,
+}
+
+impl MockRelay {
+    pub async fn run() -> Self {
+        Self::run_rejecting_kinds(Vec::new()).await
+    }
+
+    /// Events of these kinds are answered with a failed OK and not stored.
+    pub async fn run_rejecting_kinds(rejected_kinds: Vec<Kind>) -> Self {
+        Self::start(rejected_kinds, false).await
+    }
+
+    /// Misbehaving relay that answers REQ with events by any author.
+    pub async fn run_ignoring_authors() -> Self {
+        Self::start(Vec::new(), true).await
+    }
+
+    async fn start(rejected_kinds: Vec<Kind>, ignore_authors: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+
+        let (new_events, _) = broadcast::channel(1024);
+        let state = State {
+            events: Arc::default(),
+            new_events,
+            rejected_kinds,
+            ignore_authors,
+            open_subscriptions: Arc::default(),
+        };
+        let open_subscriptions = state.open_subscriptions.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, state.clone()));
+            }
+        });
+
+        Self {
+            url,
+            open_subscriptions,
+        }
+    }
+
+    /// Subscriptions of all connections that were not closed yet.
+    pub fn open_subscriptions(&self) -> usize {
+        self.open_subscriptions.load(Ordering::SeqCst)
+    }
+}
+
+/// Url of a local port nothing listens on.
+pub async fn unreachable_relay_url() -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap()
+}
+
+/// CLI context with a fresh in-memory database, no keys and no relays.
+#[cfg(feature = "cli")]
+pub async fn cli_context() -> prediction_market_event_nostr_client::cli::Context {
+    use prediction_market_event_nostr_client::cli::{db::initialize_db, Context};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // Every connection to `sqlite::memory:` is a database of its own.
+    let db_pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    initialize_db(&db_pool).await.unwrap();
+
+    Context::new(db_pool, None)
+}
+
+/// File of its own in the temp dir, removed again on drop.
+pub struct TempFile {
+    pub path: std::path::PathBuf,
+}
+
+impl TempFile {
+    pub fn new(name: &str, contents: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "prediction_market_event_client_test_{name}_{}",
+            nostr_sdk::Keys::generate().public_key()
+        ));
+        std::fs::write(&path, contents).unwrap();
+
+        Self { path }
+    }
+
+    pub fn path_str(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Runs a CLI command, args without the binary name.
+#[cfg(feature = "cli")]
+pub async fn run_cli(
+    context: &prediction_market_event_nostr_client::cli::Context,
+    args: &[&str],
+) -> anyhow::Result<serde_json::Value> {
+    use clap::Parser;
+    use prediction_market_event_nostr_client::cli::parser::Cli;
+
+    let cli = Cli::try_parse_from(["prediction_market_event_cli"].iter().chain(args))?;
+
+    cli.handle(context).await
+}
+
+async fn handle_connection(mut stream: TcpStream, state: State) {
+    if !is_websocket_upgrade(&stream).await {
+        let body = format!(r#"{{"name":"{MOCK_RELAY_NAME}","supported_nips":[1,11]}}"#);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/nostr+json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return;
+    }
+
+    let Ok(websocket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sender, mut receiver) = websocket.split();
+    let mut new_event_receiver = state.new_events.subscribe();
+    let mut subscriptions = Subscriptions {
+        filters: HashMap::new(),
+        open_subscriptions: state.open_subscriptions.clone(),
+    };
+
+    loop {
+        let relay_messages = tokio::select! {
+            message = receiver.next() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let Ok(client_message) = ClientMessage::from_json(text) else {
+                    continue;
+                };
+
+                match client_message {
+                    ClientMessage::Event(event) if state.rejected_kinds.contains(&event.kind) => {
+                        vec![RelayMessage::ok(event.id, false, "blocked: kind not allowed")]
+                    }
+                    ClientMessage::Event(event) => {
+                        state.events.lock().unwrap().push(*event.clone());
+                        let _ = state.new_events.send(*event.clone());
+
+                        vec![RelayMessage::ok(event.id, true, "")]
+                    }
+                    ClientMessage::Req {
+                        subscription_id,
+                        filters,
+                    } => {
+                        let mut relay_messages: Vec<RelayMessage> =
+                            stored_events(&state, &filters)
+                                .into_iter()
+                                .map(|event| RelayMessage::event(subscription_id.clone(), event))
+                                .collect();
+                        relay_messages.push(RelayMessage::eose(subscription_id.clone()));
+                        subscriptions.insert(subscription_id, filters);
+
+                        relay_messages
+                    }
+                    ClientMessage::Close(subscription_id) => {
+                        subscriptions.remove(&subscription_id);
+
+                        vec![]
+                    }
+                    _ => vec![],
+                }
+            }
+            event = new_event_receiver.recv() => {
+                let Ok(event) = event else {
+                    continue;
+                };
+
+                subscriptions
+                    .filters
+                    .iter()
+                    .filter(|(_, filters)| filters.iter().any(|filter| filter.match_event(&event)))
+                    .map(|(subscription_id, _)| {
+                        RelayMessage::event(subscription_id.clone(), event.clone())
+                    })
+                    .collect()
+            }
+        };
+
+        for relay_message in relay_messages {
+            if sender
+                .send(Message::Text(relay_message.as_json()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Subscriptions of one connection, counted in [MockRelay::open_subscriptions] until closed or disconnected.
+struct Subscriptions {
+    filters: HashMap<SubscriptionId, Vec<Filter>>,
+    open_subscriptions: Arc<AtomicUsize>,
+}
+
+impl Subscriptions {
+    fn insert(&mut self, subscription_id: SubscriptionId, filters: Vec<Filter>) {
+        if self.filters.insert(subscription_id, filters).is_none() {
+            self.open_subscriptions.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn remove(&mut self, subscription_id: &SubscriptionId) {
+        if self.filters.remove(subscription_id).is_some() {
+            self.open_subscriptions.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        self.open_subscriptions
+            .fetch_sub(self.filters.len(), Ordering::SeqCst);
+    }
+}
+
+/// Matching stored events newest first, each filter's limit applies to its own matches.
+fn stored_events(state: &State, filters: &[Filter]) -> Vec<Event> {
+    let mut events = state.events.lock().unwrap().clone();
+    events.sort_by_key(|event| Reverse(event.created_at));
+
+    let mut matching_events: Vec<Event> = Vec::new();
+    for filter in filters {
+        let mut filter = filter.clone();
+        if state.ignore_authors {
+            filter.authors = None;
+        }
+        let filter_events = events
+            .iter()
+            .filter(|event| filter.match_event(event))
+            .take(filter.limit.unwrap_or(usize::MAX));
+        for event in filter_events {
+            if !matching_events.contains(event) {
+                matching_events.push(event.clone());
+            }
+        }
+    }
+
+    matching_events
+}
+
+/// Peeks at the request headers without consuming them.
+async fn is_websocket_upgrade(stream: &TcpStream) -> bool {
+    let mut buf = [0; 4096];
+    loop {
+        let Ok(n) = stream.peek(&mut buf).await else {
+            return false;
+        };
+        let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+        if request.contains("\r\n\r\n") || n == buf.len() {
+            return request.contains("upgrade: websocket");
+        }
+        if n == 0 {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+    }
+}
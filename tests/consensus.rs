@@ -0,0 +1,123 @@
+use prediction_market_event::{
+    information::Information, nostr_event_types::NostrPublicKeyHex, Event, EventPayout, PayoutUnit,
+};
+use prediction_market_event_nostr_client::{Consensus, EventStatus, Quorum};
+
+fn pledger(name: &str) -> NostrPublicKeyHex {
+    NostrPublicKeyHex(name.to_owned())
+}
+
+/// Status of a two outcome event pledged to by `pledgers`, attestations give the units of outcome 0.
+fn event_status(pledgers: &[&str], attestations: &[(&str, PayoutUnit)]) -> (Event, EventStatus) {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_hash_hex = event.hash_hex().unwrap();
+    let pledges = pledgers
+        .iter()
+        .map(|name| (pledger(name), event_hash_hex.clone()));
+    let attestations: Vec<_> = attestations
+        .iter()
+        .map(|(name, units)| {
+            (
+                pledger(name),
+                EventPayout::new(&event, vec![*units, 100 - units]).unwrap(),
+            )
+        })
+        .collect();
+
+    let event_status = EventStatus::new(event.clone(), pledges, attestations).unwrap();
+
+    (event, event_status)
+}
+
+fn group_sizes(consensus: &Consensus) -> Vec<usize> {
+    consensus
+        .groups
+        .iter()
+        .map(|group| group.attestors.len())
+        .collect()
+}
+
+#[test]
+fn quorum_reached() {
+    let (event, event_status) = event_status(
+        &["alice", "bob", "carol"],
+        &[("alice", 100), ("bob", 100), ("carol", 0)],
+    );
+
+    let consensus = event_status.consensus(Quorum { k: 2 });
+
+    assert!(consensus.is_reached());
+    assert_eq!(
+        consensus.winning_payout,
+        Some(EventPayout::new(&event, vec![100, 0]).unwrap())
+    );
+    assert_eq!(group_sizes(&consensus), vec![2, 1]);
+    assert_eq!(
+        consensus.groups[0].attestors,
+        vec![pledger("alice"), pledger("bob")]
+    );
+    assert!(consensus.conflicting_pledgers.is_empty());
+}
+
+#[test]
+fn quorum_not_reached() {
+    let (_, event_status) = event_status(
+        &["alice", "bob", "carol"],
+        &[("alice", 100), ("bob", 100), ("carol", 0)],
+    );
+
+    let consensus = event_status.consensus(Quorum { k: 3 });
+
+    assert!(!consensus.is_reached());
+    assert_eq!(consensus.winning_payout, None);
+}
+
+#[test]
+fn tie_between_payout_groups_is_no_consensus() {
+    let (_, event_status) = event_status(
+        &["alice", "bob", "carol", "dave"],
+        &[("alice", 100), ("bob", 100), ("carol", 0), ("dave", 0)],
+    );
+
+    let consensus = event_status.consensus(Quorum { k: 2 });
+
+    assert!(!consensus.is_reached());
+    assert_eq!(group_sizes(&consensus), vec![2, 2]);
+}
+
+#[test]
+fn conflicting_pledger_is_excluded() {
+    let (event, event_status) = event_status(
+        &["alice", "bob", "carol"],
+        &[("alice", 100), ("bob", 0), ("bob", 100), ("carol", 100)],
+    );
+
+    let consensus = event_status.consensus(Quorum { k: 2 });
+
+    assert_eq!(consensus.conflicting_pledgers, vec![pledger("bob")]);
+    assert_eq!(group_sizes(&consensus), vec![2]);
+    assert_eq!(
+        consensus.groups[0].attestors,
+        vec![pledger("alice"), pledger("carol")]
+    );
+    assert_eq!(
+        consensus.winning_payout,
+        Some(EventPayout::new(&event, vec![100, 0]).unwrap())
+    );
+    assert!(!event_status.consensus(Quorum { k: 3 }).is_reached());
+}
+
+#[test]
+fn attestations_of_non_pledgers_are_ignored() {
+    let (_, event_status) = event_status(
+        &["alice", "bob"],
+        &[("alice", 100), ("mallory", 0), ("trudy", 0)],
+    );
+
+    let consensus = event_status.consensus(Quorum { k: 1 });
+
+    assert_eq!(group_sizes(&consensus), vec![1]);
+    assert_eq!(consensus.groups[0].attestors, vec![pledger("alice")]);
+    assert!(consensus.is_reached());
+    assert_eq!(event_status.pending_pledgers, vec![pledger("bob")]);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::Keys;
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{FutureEventPayoutAttestationPledge, NewEvent, NostrPublicKeyHex},
+    Event,
+};
+use prediction_market_event_nostr_client::Client;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+#[tokio::test]
+async fn event_status_skips_pledges_deleted_by_their_author() {
+    let relay = MockRelay::run().await;
+    let creator_keys = Keys::generate();
+    let retracting_keys = Keys::generate();
+    let creator = Client::new_initialized_client_signer(
+        vec![(relay.url.clone(), None)],
+        creator_keys.clone(),
+    )
+    .await
+    .unwrap();
+    let retracting_pledger = Client::new_initialized_client_signer(
+        vec![(relay.url.clone(), None)],
+        retracting_keys.clone(),
+    )
+    .await
+    .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_hash_hex = event.hash_hex().unwrap();
+    creator.publish::<NewEvent>(&event).await.unwrap();
+    let creator_pledge = creator
+        .publish::<FutureEventPayoutAttestationPledge>(&event_hash_hex)
+        .await
+        .unwrap();
+    let retracted_pledge = retracting_pledger
+        .publish::<FutureEventPayoutAttestationPledge>(&event_hash_hex)
+        .await
+        .unwrap();
+
+    retracting_pledger
+        .delete([retracted_pledge.event_id], Some("wrong event"))
+        .await
+        .unwrap();
+    // Only the author of an event can delete it.
+    retracting_pledger
+        .delete([creator_pledge.event_id], None)
+        .await
+        .unwrap();
+
+    let event_status = creator
+        .event_status(&event_hash_hex, TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        event_status.pledgers,
+        vec![NostrPublicKeyHex(creator_keys.public_key.to_hex())]
+    );
+}
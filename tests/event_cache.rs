@@ -0,0 +1,161 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli, MockRelay};
+use nostr_sdk::{EventBuilder, EventId, Filter, Keys, Kind, Tag, Timestamp};
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent},
+    Event, EventPayout,
+};
+use prediction_market_event_nostr_client::{
+    cli::{db::NostrEventCache, Context},
+    Client,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+fn nostr_event(keys: &Keys, kind: Kind, created_at: u64, hashtags: &[&str]) -> nostr_sdk::Event {
+    EventBuilder::new(
+        kind,
+        format!("{kind} {created_at}"),
+        hashtags.iter().map(|hashtag| Tag::hashtag(*hashtag)),
+    )
+    .custom_created_at(Timestamp::from(created_at))
+    .to_event(keys)
+    .unwrap()
+}
+
+async fn cached_ids(context: &Context, filter: Filter) -> Vec<EventId> {
+    NostrEventCache::get_events(context, &[filter])
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|nostr_event| nostr_event.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn cached_events_are_found_by_every_filter_field() {
+    let context = cli_context().await;
+    let alice = Keys::generate();
+    let bob = Keys::generate();
+    let old_note = nostr_event(&alice, Kind::TextNote, NOW - 20, &["first", "shared"]);
+    let new_note = nostr_event(&bob, Kind::TextNote, NOW - 10, &["second", "shared"]);
+    let custom = nostr_event(&alice, Kind::Custom(30), NOW, &[]);
+    NostrEventCache::put_events(
+        &context,
+        &[old_note.clone(), new_note.clone(), custom.clone()],
+    )
+    .await
+    .unwrap();
+    // Already cached events are ignored.
+    NostrEventCache::put_events(&context, std::slice::from_ref(&old_note))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        cached_ids(&context, Filter::new()).await,
+        vec![custom.id, new_note.id, old_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().ids([old_note.id, custom.id])).await,
+        vec![custom.id, old_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().author(bob.public_key())).await,
+        vec![new_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().kind(Kind::TextNote)).await,
+        vec![new_note.id, old_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().since(Timestamp::from(NOW - 10))).await,
+        vec![custom.id, new_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().until(Timestamp::from(NOW - 10))).await,
+        vec![new_note.id, old_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().hashtag("second")).await,
+        vec![new_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().hashtag("shared")).await,
+        vec![new_note.id, old_note.id]
+    );
+    assert_eq!(
+        cached_ids(&context, Filter::new().hashtag("shared").limit(1)).await,
+        vec![new_note.id]
+    );
+    assert_eq!(
+        cached_ids(
+            &context,
+            Filter::new()
+                .kind(Kind::TextNote)
+                .author(alice.public_key())
+                .hashtag("shared")
+        )
+        .await,
+        vec![old_note.id]
+    );
+}
+
+#[tokio::test]
+async fn offline_event_status_is_answered_from_the_cache() {
+    let relay = MockRelay::run().await;
+    let creator = Keys::generate();
+    let pledger = Keys::generate();
+    let retracting_pledger = Keys::generate();
+    let mut clients = Vec::new();
+    for keys in [&creator, &pledger, &retracting_pledger] {
+        let client =
+            Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], keys.clone())
+                .await
+                .unwrap();
+        clients.push(client);
+    }
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_hash_hex = event.hash_hex().unwrap();
+    clients[0].publish::<NewEvent>(&event).await.unwrap();
+    clients[1]
+        .publish::<FutureEventPayoutAttestationPledge>(&event_hash_hex)
+        .await
+        .unwrap();
+    let retracted_pledge = clients[2]
+        .publish::<FutureEventPayoutAttestationPledge>(&event_hash_hex)
+        .await
+        .unwrap();
+    clients[2]
+        .delete([retracted_pledge.event_id], None)
+        .await
+        .unwrap();
+    let event_payout = EventPayout::new(&event, vec![100, 0]).unwrap();
+    clients[1]
+        .publish::<EventPayoutAttestation>(&event_payout)
+        .await
+        .unwrap();
+
+    let context = cli_context().await;
+    let offline_args = ["query", "--offline", "event-status", &event_hash_hex.0];
+    assert!(run_cli(&context, &offline_args).await.is_err());
+
+    run_cli(&context, &["relay", "add", relay.url.as_str()])
+        .await
+        .unwrap();
+    let online = run_cli(&context, &["query", "event-status", &event_hash_hex.0])
+        .await
+        .unwrap();
+    assert_eq!(
+        online["pledgers"],
+        serde_json::json!([pledger.public_key().to_hex()])
+    );
+    assert_eq!(online["pending_pledgers"], serde_json::json!([]));
+
+    run_cli(&context, &["relay", "remove-all"]).await.unwrap();
+    let offline = run_cli(&context, &offline_args).await.unwrap();
+    assert_eq!(offline, online);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::{EventBuilder, Keys, Kind, Tag, Timestamp};
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{NewEvent, NostrEventUtils},
+    Event,
+};
+use prediction_market_event_nostr_client::Client;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+#[tokio::test]
+async fn event_status_ignores_events_that_only_mention_the_hash() {
+    let relay = MockRelay::run().await;
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], Keys::generate())
+            .await
+            .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_hash_hex = event.hash_hex().unwrap();
+    let decoy = Event::new_with_random_nonce(3, 100, Information::None);
+    // Valid new event of the decoy that is also tagged with the hash of the event, newer so relays return it first.
+    let decoy_nostr_event = EventBuilder::new(
+        Kind::from(NewEvent::KIND_U16),
+        decoy.try_to_json_string().unwrap(),
+        [
+            Tag::hashtag(decoy.hash_hex().unwrap().0),
+            Tag::hashtag(event_hash_hex.0.clone()),
+        ],
+    )
+    .custom_created_at(Timestamp::now() + 60)
+    .to_event(&Keys::generate())
+    .unwrap();
+    assert!(NewEvent::interpret_nostr_event(&decoy_nostr_event).is_ok());
+    client.broadcast(decoy_nostr_event).await.unwrap();
+
+    assert!(client
+        .event_status(&event_hash_hex, TIMEOUT)
+        .await
+        .unwrap()
+        .is_none());
+
+    client.publish::<NewEvent>(&event).await.unwrap();
+    let event_status = client
+        .event_status(&event_hash_hex, TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event_status.event, event);
+}
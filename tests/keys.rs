@@ -0,0 +1,122 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli};
+use nostr_sdk::{Keys, ToBech32};
+use prediction_market_event_nostr_client::cli::{
+    stdin_prompts::{BIP39_PASSPHRASE_ENV_VAR, MNEMONIC_ENV_VAR, PASSPHRASE_ENV_VAR},
+    Context,
+};
+
+/// Every test uses the same passphrase, the environment is shared by all tests of the crate.
+const PASSPHRASE: &str = "correct horse battery staple";
+
+fn set_passphrase() {
+    std::env::set_var(PASSPHRASE_ENV_VAR, PASSPHRASE);
+}
+
+#[tokio::test]
+async fn set_key_of_encrypted_identity_stays_encrypted() {
+    set_passphrase();
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+    run_cli(&context, &["key", "encrypt"]).await.unwrap();
+
+    let keys = Keys::generate();
+    run_cli(
+        &context,
+        &["key", "set", &keys.secret_key().to_secret_hex()],
+    )
+    .await
+    .unwrap();
+
+    let identities = run_cli(&context, &["key", "list"]).await.unwrap();
+    assert_eq!(identities[0]["encrypted"], true);
+    assert_eq!(
+        run_cli(&context, &["key", "public"]).await.unwrap(),
+        keys.public_key().to_bech32().unwrap()
+    );
+}
+
+/// Values of the mnemonic table as stored.
+async fn stored_mnemonics(context: &Context) -> Vec<String> {
+    sqlx::query_scalar("SELECT v FROM nostr_mnemonic")
+        .fetch_all(&context.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn mnemonic_is_kept_encrypted_with_the_key() {
+    set_passphrase();
+    let context = cli_context().await;
+    let generated = run_cli(&context, &["key", "generate", "--mnemonic"])
+        .await
+        .unwrap();
+    let mnemonic = generated["mnemonic"].as_str().unwrap();
+
+    run_cli(&context, &["key", "encrypt"]).await.unwrap();
+    let stored = stored_mnemonics(&context).await;
+    assert_eq!(stored.len(), 1);
+    assert!(!stored[0].contains(mnemonic));
+    let exported = run_cli(&context, &["key", "export-mnemonic"])
+        .await
+        .unwrap();
+    assert_eq!(exported["mnemonic"], mnemonic);
+
+    run_cli(&context, &["key", "decrypt"]).await.unwrap();
+    assert!(stored_mnemonics(&context).await[0].contains(mnemonic));
+    let exported = run_cli(&context, &["key", "export-mnemonic"])
+        .await
+        .unwrap();
+    assert_eq!(exported["mnemonic"], mnemonic);
+}
+
+#[tokio::test]
+async fn import_mnemonic_replaces_a_key_only_with_force() {
+    set_passphrase();
+    let context = cli_context().await;
+    let generated = run_cli(&context, &["key", "generate"]).await.unwrap();
+    run_cli(&context, &["key", "encrypt"]).await.unwrap();
+
+    let mnemonic = "leader monkey parrot ring guide accident before fence cannon height naive bean";
+    // Only this test imports a mnemonic.
+    std::env::set_var(MNEMONIC_ENV_VAR, mnemonic);
+    std::env::set_var(BIP39_PASSPHRASE_ENV_VAR, "bip39 passphrase");
+    let error = run_cli(&context, &["key", "import-mnemonic"])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("--force"), "{error}");
+    assert_eq!(
+        run_cli(&context, &["key", "public"]).await.unwrap(),
+        generated["public_key"]
+    );
+
+    let public_key = run_cli(&context, &["key", "import-mnemonic", "--force"])
+        .await
+        .unwrap();
+    assert_eq!(
+        run_cli(&context, &["key", "public"]).await.unwrap(),
+        public_key
+    );
+    let identities = run_cli(&context, &["key", "list"]).await.unwrap();
+    assert_eq!(identities[0]["encrypted"], true);
+    assert!(!stored_mnemonics(&context).await[0].contains(mnemonic));
+    let exported = run_cli(&context, &["key", "export-mnemonic"])
+        .await
+        .unwrap();
+    assert_eq!(exported["mnemonic"], mnemonic);
+
+    let with_passphrase = run_cli(
+        &context,
+        &["key", "import-mnemonic", "--passphrase", "--force"],
+    )
+    .await
+    .unwrap();
+    assert_ne!(with_passphrase, public_key);
+    let exported = run_cli(&context, &["key", "export-mnemonic"])
+        .await
+        .unwrap();
+    assert_eq!(exported["passphrase_required"], true);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli, TempFile};
+use prediction_market_event::{information::Information, Event};
+use prediction_market_event_nostr_client::cli::Context;
+
+async fn context_with_key() -> Context {
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+
+    context
+}
+
+async fn dry_run_from_file(
+    context: &Context,
+    name: &str,
+    json: &str,
+    args: &[&str],
+) -> anyhow::Result<serde_json::Value> {
+    let file = TempFile::new(&format!("{name}.json"), json);
+    let mut cli_args = vec![
+        "publish",
+        "--dry-run",
+        "new-event",
+        "--from-file",
+        file.path_str(),
+    ];
+    cli_args.extend_from_slice(args);
+
+    run_cli(context, &cli_args).await
+}
+
+#[tokio::test]
+async fn full_event_from_file() {
+    let context = context_with_key().await;
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let json = event.try_to_json_string().unwrap();
+
+    let dry_run = dry_run_from_file(&context, "event", &json, &[])
+        .await
+        .unwrap();
+    assert_eq!(dry_run["hash_hex"], event.hash_hex().unwrap().0);
+
+    let e = dry_run_from_file(&context, "event_with_args", &json, &["2", "100"])
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("full event"), "{e}");
+}
+
+#[tokio::test]
+async fn information_from_file_needs_outcome_count_and_units() {
+    let context = context_with_key().await;
+    let json = serde_json::to_string(&Information::None).unwrap();
+
+    let dry_run = dry_run_from_file(&context, "information", &json, &["2", "100"])
+        .await
+        .unwrap();
+    assert!(dry_run["hash_hex"].is_string());
+
+    let e = dry_run_from_file(&context, "information_without_args", &json, &[])
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("required"), "{e}");
+}
+
+#[tokio::test]
+async fn information_type_conflicts_with_from_file() {
+    let context = context_with_key().await;
+    let json = serde_json::to_string(&Information::None).unwrap();
+
+    let e = dry_run_from_file(&context, "information_type", &json, &["2", "100", "none"])
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("cannot be used with"), "{e}");
+}
+
+#[tokio::test]
+async fn invalid_file_reports_both_parse_errors() {
+    let context = context_with_key().await;
+
+    let e = dry_run_from_file(&context, "invalid", r#"{"not": "an event"}"#, &["2", "100"])
+        .await
+        .unwrap_err();
+    let message = e.to_string();
+    assert!(message.contains("neither an event ("), "{message}");
+    assert!(message.contains(") nor information ("), "{message}");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::{
+    nips::nip46::Request,
+    signer::{Nip46Signer, NostrConnectRemoteSigner, NostrConnectSignerActions},
+    Keys, NostrSigner,
+};
+use prediction_market_event::{information::Information, nostr_event_types::NewEvent, Event};
+use prediction_market_event_nostr_client::Client;
+
+struct ApproveAll;
+
+impl NostrConnectSignerActions for ApproveAll {
+    fn approve(&self, _req: &Request) -> bool {
+        true
+    }
+}
+
+/// Starts a bunker serving `keys` over the relay and returns a signer connected to it.
+async fn bunker_signer(relay: &MockRelay, keys: &Keys) -> NostrSigner {
+    let remote_signer = NostrConnectRemoteSigner::new(
+        keys.secret_key().clone(),
+        [relay.url.clone()],
+        Some("secret".to_owned()),
+        None,
+    )
+    .await
+    .unwrap();
+    let bunker_uri = remote_signer.bunker_uri().await;
+    tokio::spawn(async move { remote_signer.serve(ApproveAll).await });
+
+    let signer = Nip46Signer::new(bunker_uri, Keys::generate(), Duration::from_secs(10), None)
+        .await
+        .unwrap();
+
+    NostrSigner::nip46(signer)
+}
+
+#[tokio::test]
+async fn publish_with_nip46_signer() {
+    let relay = MockRelay::run().await;
+    let bunker_keys = Keys::generate();
+    let signer = bunker_signer(&relay, &bunker_keys).await;
+
+    let client = Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], signer)
+        .await
+        .unwrap();
+    assert_eq!(client.public_key().await.unwrap(), bunker_keys.public_key);
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let report = client.publish::<NewEvent>(&event).await.unwrap();
+    assert!(report.success.contains(&relay.url));
+
+    let events = client
+        .get::<NewEvent>(
+            |f| vec![f.id(report.event_id)],
+            Some(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap();
+    let [(nostr_event, fetched_event)] = events.as_slice() else {
+        panic!("expected exactly one event, got {}", events.len());
+    };
+    assert_eq!(nostr_event.pubkey, bunker_keys.public_key);
+    assert!(nostr_event.verify().is_ok());
+    assert_eq!(fetched_event, &event);
+}
+
+#[tokio::test]
+async fn sign_with_local_keys_and_nip46_signer_is_equivalent() {
+    let relay = MockRelay::run().await;
+    let keys = Keys::generate();
+    let signer = bunker_signer(&relay, &keys).await;
+
+    let local_client = Client::new_initialized_client_signer(Vec::new(), keys.clone())
+        .await
+        .unwrap();
+    let remote_client = Client::new_initialized_client_signer(Vec::new(), signer)
+        .await
+        .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let local_nostr_event = local_client.sign::<NewEvent>(&event).await.unwrap();
+    let remote_nostr_event = remote_client.sign::<NewEvent>(&event).await.unwrap();
+
+    assert_eq!(local_nostr_event.pubkey, remote_nostr_event.pubkey);
+    assert_eq!(local_nostr_event.kind, remote_nostr_event.kind);
+    assert_eq!(local_nostr_event.content, remote_nostr_event.content);
+    assert!(remote_nostr_event.verify().is_ok());
+}
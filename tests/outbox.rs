@@ -0,0 +1,144 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::collections::HashSet;
+
+use common::{cli_context, run_cli, MockRelay};
+use nostr_sdk::{EventBuilder, Keys, Kind, Timestamp};
+use prediction_market_event::nostr_event_types::{NewEvent, NostrEventUtils};
+use prediction_market_event_nostr_client::cli::{db::Outbox, success_exit_code, QUEUED_EXIT_CODE};
+
+#[tokio::test]
+async fn attempts_back_off_exponentially_up_to_a_day() {
+    let context = cli_context().await;
+    let nostr_event = EventBuilder::text_note("outbox", [])
+        .to_event(&Keys::generate())
+        .unwrap();
+
+    let mut delays = Vec::new();
+    for _ in 0..13 {
+        let before = Timestamp::now().as_u64();
+        let entry = Outbox::record_attempt(&context, &nostr_event, &HashSet::new(), None)
+            .await
+            .unwrap();
+        delays.push(entry.next_attempt_at - before);
+    }
+    // Seconds may tick over between reading the clock here and in the outbox.
+    let expected = [
+        60, 120, 240, 480, 960, 1920, 3840, 7680, 15360, 30720, 61440, 86400, 86400,
+    ];
+    for (delay, expected) in delays.iter().zip(expected) {
+        assert!(
+            (expected..=expected + 1).contains(delay),
+            "{delays:?} != {expected:?}"
+        );
+    }
+
+    let entries = Outbox::get_all_entries(&context).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].attempts, 13);
+    assert!(Outbox::get_due_entries(&context).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn too_few_acks_queue_the_event_until_flush_reaches_min_relay_acks() {
+    let accepting_relay = MockRelay::run().await;
+    let rejecting_relay =
+        MockRelay::run_rejecting_kinds(vec![Kind::from(NewEvent::KIND_U16)]).await;
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+    for relay in [&accepting_relay, &rejecting_relay] {
+        run_cli(&context, &["relay", "add", relay.url.as_str()])
+            .await
+            .unwrap();
+    }
+    run_cli(&context, &["outbox", "min-acks", "2"])
+        .await
+        .unwrap();
+
+    let json = run_cli(&context, &["publish", "new-event", "2", "100", "none"])
+        .await
+        .unwrap();
+    assert_eq!(json["queued"], true);
+    assert_eq!(json["relays"], serde_json::json!([accepting_relay.url]));
+    assert!(json["hash_hex"].is_string());
+    assert_eq!(success_exit_code(&json), QUEUED_EXIT_CODE);
+    let event_id = json["event_id"].as_str().unwrap();
+
+    let entries = run_cli(&context, &["outbox", "list"]).await.unwrap();
+    assert_eq!(entries[0]["event_id"], event_id);
+    assert_eq!(entries[0]["attempts"], 1);
+    assert_eq!(
+        entries[0]["acked_relays"],
+        serde_json::json!([accepting_relay.url])
+    );
+
+    // The backoff has not passed yet.
+    let results = run_cli(&context, &["outbox", "flush"]).await.unwrap();
+    assert_eq!(results, serde_json::json!([]));
+
+    let results = run_cli(&context, &["outbox", "flush", "--all"])
+        .await
+        .unwrap();
+    assert_eq!(results[0]["sent"], false);
+    assert_eq!(results[0]["attempts"], 2);
+    assert!(results[0]["last_error"]
+        .as_str()
+        .unwrap()
+        .contains("blocked: kind not allowed"));
+
+    run_cli(&context, &["outbox", "min-acks", "1"])
+        .await
+        .unwrap();
+    let results = run_cli(&context, &["outbox", "flush", "--all"])
+        .await
+        .unwrap();
+    assert_eq!(results[0]["event_id"], event_id);
+    assert_eq!(results[0]["sent"], true);
+    assert_eq!(
+        run_cli(&context, &["outbox", "list"]).await.unwrap(),
+        serde_json::json!([])
+    );
+}
+
+#[tokio::test]
+async fn send_rejected_by_every_relay_is_queued_with_the_error() {
+    let relay = MockRelay::run_rejecting_kinds(vec![Kind::from(NewEvent::KIND_U16)]).await;
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+    run_cli(&context, &["relay", "add", relay.url.as_str()])
+        .await
+        .unwrap();
+
+    let json = run_cli(&context, &["publish", "new-event", "2", "100", "none"])
+        .await
+        .unwrap();
+    assert_eq!(json["queued"], true);
+    assert_eq!(json["relays"], serde_json::json!([]));
+    assert_eq!(
+        json["failed_relays"][relay.url.as_str()],
+        "blocked: kind not allowed"
+    );
+    assert!(json["hash_hex"].is_string());
+
+    let entries = run_cli(&context, &["outbox", "list"]).await.unwrap();
+    assert_eq!(entries[0]["event_id"], json["event_id"]);
+    assert!(entries[0]["last_error"]
+        .as_str()
+        .unwrap()
+        .contains("blocked: kind not allowed"));
+}
+
+#[tokio::test]
+async fn min_acks_of_zero_is_rejected() {
+    let context = cli_context().await;
+
+    assert!(run_cli(&context, &["outbox", "min-acks", "0"])
+        .await
+        .is_err());
+    assert_eq!(
+        run_cli(&context, &["outbox", "min-acks"]).await.unwrap(),
+        serde_json::json!(1)
+    );
+}
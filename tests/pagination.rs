@@ -0,0 +1,176 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use futures_util::TryStreamExt;
+use nostr_sdk::{EventBuilder, Filter, Keys, Kind, Timestamp};
+use prediction_market_event_nostr_client::Client;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+const KIND: Kind = Kind::TextNote;
+const SPARSE_KIND: Kind = Kind::Custom(30);
+
+const PAGE_SIZE: usize = 10;
+
+const NOW: u64 = 1_700_000_000;
+const YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Sends one event per `(kind, content, created_at)` to the relay.
+async fn publish_events(relay: &MockRelay, events: impl IntoIterator<Item = (Kind, String, u64)>) {
+    let keys = Keys::generate();
+    let publisher = nostr_sdk::Client::default();
+    publisher.add_relay(relay.url.clone()).await.unwrap();
+    publisher.connect().await;
+
+    for (kind, content, created_at) in events {
+        let nostr_event = EventBuilder::new(kind, content, [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(&keys)
+            .unwrap();
+        publisher.send_event(nostr_event).await.unwrap();
+    }
+}
+
+/// Sorted contents of all pages, checks that no event is in more than one page.
+async fn get_all_contents(client: &Client, filters: Vec<Filter>) -> Vec<String> {
+    let pages: Vec<Vec<nostr_sdk::Event>> = client
+        .get_nostr_event_pages(filters, PAGE_SIZE, TIMEOUT)
+        .try_collect()
+        .await
+        .unwrap();
+
+    let mut contents: Vec<String> = pages
+        .into_iter()
+        .flatten()
+        .map(|nostr_event| nostr_event.content)
+        .collect();
+    contents.sort();
+    let count = contents.len();
+    contents.dedup();
+    assert_eq!(
+        contents.len(),
+        count,
+        "event returned in more than one page"
+    );
+
+    contents
+}
+
+fn contents(prefix: &str, count: u64) -> Vec<String> {
+    let mut contents: Vec<String> = (0..count).map(|i| format!("{prefix}{i:02}")).collect();
+    contents.sort();
+
+    contents
+}
+
+#[tokio::test]
+async fn pages_return_every_event_once() {
+    let relay = MockRelay::run().await;
+    // Two per timestamp so pages overlap at the `until` boundary.
+    publish_events(
+        &relay,
+        (0..25).map(|i| (KIND, format!("dense{i:02}"), NOW + i / 2)),
+    )
+    .await;
+
+    let client = Client::new_initialized_client_query_only(vec![(relay.url.clone(), None)])
+        .await
+        .unwrap();
+
+    let single_request = client
+        .get_nostr_events(vec![Filter::new().kind(KIND).limit(PAGE_SIZE)], TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(single_request.len(), PAGE_SIZE);
+
+    let all_contents =
+        get_all_contents(&client, vec![Filter::new().kind(KIND).limit(PAGE_SIZE)]).await;
+    assert_eq!(all_contents, contents("dense", 25));
+}
+
+#[tokio::test]
+async fn dense_relay_is_not_skipped_because_of_sparse_relay() {
+    let dense_relay = MockRelay::run().await;
+    let sparse_relay = MockRelay::run().await;
+    publish_events(
+        &dense_relay,
+        (0..25).map(|i| (KIND, format!("dense{i:02}"), NOW + i)),
+    )
+    .await;
+    publish_events(
+        &sparse_relay,
+        (0..3).map(|i| (KIND, format!("sparse{i:02}"), NOW - YEAR + i)),
+    )
+    .await;
+
+    let client = Client::new_initialized_client_query_only(vec![
+        (dense_relay.url.clone(), None),
+        (sparse_relay.url.clone(), None),
+    ])
+    .await
+    .unwrap();
+    // Both relays are connected once they answered a request.
+    let all_nostr_events = client
+        .get_nostr_events(vec![Filter::new().kind(KIND)], TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(all_nostr_events.len(), 28);
+
+    let all_contents = get_all_contents(&client, vec![Filter::new().kind(KIND)]).await;
+    let mut expected = contents("dense", 25);
+    expected.extend(contents("sparse", 3));
+    assert_eq!(all_contents, expected);
+}
+
+#[tokio::test]
+async fn dense_filter_is_not_skipped_because_of_sparse_filter() {
+    let relay = MockRelay::run().await;
+    publish_events(
+        &relay,
+        (0..25).map(|i| (KIND, format!("dense{i:02}"), NOW + i)),
+    )
+    .await;
+    publish_events(
+        &relay,
+        (0..3).map(|i| (SPARSE_KIND, format!("sparse{i:02}"), NOW - YEAR + i)),
+    )
+    .await;
+
+    let client = Client::new_initialized_client_query_only(vec![(relay.url.clone(), None)])
+        .await
+        .unwrap();
+
+    let all_contents = get_all_contents(
+        &client,
+        vec![Filter::new().kind(KIND), Filter::new().kind(SPARSE_KIND)],
+    )
+    .await;
+    let mut expected = contents("dense", 25);
+    expected.extend(contents("sparse", 3));
+    assert_eq!(all_contents, expected);
+}
+
+#[tokio::test]
+async fn full_page_with_one_timestamp_does_not_end_the_stream() {
+    let relay = MockRelay::run().await;
+    publish_events(
+        &relay,
+        (0..5).map(|i| (KIND, format!("older{i:02}"), NOW - 10 - i)),
+    )
+    .await;
+    publish_events(&relay, (0..15).map(|i| (KIND, format!("same{i:02}"), NOW))).await;
+
+    let client = Client::new_initialized_client_query_only(vec![(relay.url.clone(), None)])
+        .await
+        .unwrap();
+
+    let all_contents = get_all_contents(&client, vec![Filter::new().kind(KIND)]).await;
+    let older_contents: Vec<String> = all_contents
+        .iter()
+        .filter(|content| content.starts_with("older"))
+        .cloned()
+        .collect();
+    assert_eq!(older_contents, contents("older", 5));
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::Keys;
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent},
+    Event, EventPayout,
+};
+use prediction_market_event_nostr_client::Client;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+#[tokio::test]
+async fn pending_attestations_leave_out_attested_and_deleted_pledges() {
+    let relay = MockRelay::run().await;
+    let keys = Keys::generate();
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], keys.clone())
+            .await
+            .unwrap();
+
+    let pending_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let attested_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let retracted_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let mut pledge_ids = Vec::new();
+    for event in [&pending_event, &attested_event, &retracted_event] {
+        client.publish::<NewEvent>(event).await.unwrap();
+        let report = client
+            .publish::<FutureEventPayoutAttestationPledge>(&event.hash_hex().unwrap())
+            .await
+            .unwrap();
+        pledge_ids.push(report.event_id);
+    }
+    let event_payout = EventPayout::new(&attested_event, vec![100, 0]).unwrap();
+    client
+        .publish::<EventPayoutAttestation>(&event_payout)
+        .await
+        .unwrap();
+    client
+        .delete([pledge_ids[2]], Some("wrong event"))
+        .await
+        .unwrap();
+
+    let pending_attestations = client
+        .pending_attestations(keys.public_key, TIMEOUT)
+        .await
+        .unwrap();
+
+    let events: Vec<&Event> = pending_attestations
+        .events
+        .iter()
+        .map(|(_, event)| event)
+        .collect();
+    assert_eq!(events, vec![&pending_event]);
+    assert!(pending_attestations.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn pledges_by_other_authors_are_ignored_with_a_warning() {
+    let relay = MockRelay::run_ignoring_authors().await;
+    let keys = Keys::generate();
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], keys.clone())
+            .await
+            .unwrap();
+    let other_client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], Keys::generate())
+            .await
+            .unwrap();
+
+    let own_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_event = Event::new_with_random_nonce(2, 100, Information::None);
+    for event in [&own_event, &other_event] {
+        client.publish::<NewEvent>(event).await.unwrap();
+    }
+    client
+        .publish::<FutureEventPayoutAttestationPledge>(&own_event.hash_hex().unwrap())
+        .await
+        .unwrap();
+    let other_pledge = other_client
+        .publish::<FutureEventPayoutAttestationPledge>(&other_event.hash_hex().unwrap())
+        .await
+        .unwrap();
+
+    let pending_attestations = client
+        .pending_attestations(keys.public_key, TIMEOUT)
+        .await
+        .unwrap();
+
+    let events: Vec<&Event> = pending_attestations
+        .events
+        .iter()
+        .map(|(_, event)| event)
+        .collect();
+    assert_eq!(events, vec![&own_event]);
+    assert_eq!(pending_attestations.warnings.len(), 1);
+    assert!(pending_attestations.warnings[0].contains(&other_pledge.event_id.to_hex()));
+}
+
+#[tokio::test]
+async fn more_pledges_than_fit_in_one_filter_are_all_looked_up() {
+    let relay = MockRelay::run().await;
+    let keys = Keys::generate();
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], keys.clone())
+            .await
+            .unwrap();
+
+    let mut pledged_events = Vec::new();
+    let mut pledge_ids = Vec::new();
+    for _ in 0..250 {
+        let event = Event::new_with_random_nonce(2, 100, Information::None);
+        client.publish::<NewEvent>(&event).await.unwrap();
+        let report = client
+            .publish::<FutureEventPayoutAttestationPledge>(&event.hash_hex().unwrap())
+            .await
+            .unwrap();
+        pledged_events.push(event);
+        pledge_ids.push(report.event_id);
+    }
+    // One retracted pledge in each chunk of the deletion lookup.
+    let retracted = [0, 150, 249];
+    client
+        .delete(retracted.map(|i| pledge_ids[i]), None)
+        .await
+        .unwrap();
+    for i in retracted.into_iter().rev() {
+        pledged_events.remove(i);
+    }
+
+    let pending_attestations = client
+        .pending_attestations(keys.public_key, TIMEOUT)
+        .await
+        .unwrap();
+
+    let mut pending_hashes: Vec<String> = pending_attestations
+        .events
+        .iter()
+        .map(|(_, event)| event.hash_hex().unwrap().0)
+        .collect();
+    pending_hashes.sort();
+    let mut pledged_hashes: Vec<String> = pledged_events
+        .iter()
+        .map(|event| event.hash_hex().unwrap().0)
+        .collect();
+    pledged_hashes.sort();
+    assert_eq!(pending_hashes, pledged_hashes);
+    assert!(pending_attestations.warnings.is_empty());
+}
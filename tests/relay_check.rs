@@ -0,0 +1,66 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{unreachable_relay_url, MockRelay, MOCK_RELAY_NAME};
+use nostr_sdk::Kind;
+use prediction_market_event::nostr_event_types::{
+    EventPayoutAttestation, FutureEventPayoutAttestationPledge, NewEvent, NostrEventUtils,
+};
+use prediction_market_event_nostr_client::Client;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn check_working_relay() {
+    let relay = MockRelay::run().await;
+
+    let relay_check = Client::check_relay(relay.url.clone(), TIMEOUT).await;
+
+    assert!(relay_check.connected);
+    assert!(relay_check.is_usable());
+    assert!(relay_check.latency.is_some());
+    assert_eq!(
+        relay_check.information.unwrap().name.as_deref(),
+        Some(MOCK_RELAY_NAME)
+    );
+    let kinds: Vec<u16> = relay_check.kinds.iter().map(|k| k.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            NewEvent::KIND_U16,
+            FutureEventPayoutAttestationPledge::KIND_U16,
+            EventPayoutAttestation::KIND_U16,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn check_relay_rejecting_kind() {
+    let relay =
+        MockRelay::run_rejecting_kinds(vec![Kind::from(EventPayoutAttestation::KIND_U16)]).await;
+
+    let relay_check = Client::check_relay(relay.url.clone(), TIMEOUT).await;
+
+    assert!(relay_check.connected);
+    assert!(!relay_check.is_usable());
+    for kind_check in relay_check.kinds {
+        let rejected = kind_check.kind == EventPayoutAttestation::KIND_U16;
+        assert_eq!(kind_check.accepted, !rejected);
+        assert_eq!(kind_check.returned, !rejected);
+        assert_eq!(kind_check.message.is_some(), rejected);
+    }
+}
+
+#[tokio::test]
+async fn check_unreachable_relay() {
+    let url = unreachable_relay_url().await;
+
+    let relay_check = Client::check_relay(url, TIMEOUT).await;
+
+    assert!(!relay_check.connected);
+    assert!(!relay_check.is_usable());
+    assert!(relay_check.latency.is_none());
+    assert!(relay_check.information.is_none());
+    assert!(relay_check.kinds.is_empty());
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::{nips::nip65::RelayMetadata, Keys, Kind};
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{NewEvent, NostrEventUtils},
+    Event,
+};
+use prediction_market_event_nostr_client::{Client, ClientError};
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+#[tokio::test]
+async fn publish_to_write_relays_and_get_from_read_relays() {
+    let read_relay = MockRelay::run().await;
+    let write_relay = MockRelay::run().await;
+
+    let client = Client::new_initialized_client_signer(
+        vec![
+            (read_relay.url.clone(), Some(RelayMetadata::Read)),
+            (write_relay.url.clone(), Some(RelayMetadata::Write)),
+        ],
+        Keys::generate(),
+    )
+    .await
+    .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let report = client.publish::<NewEvent>(&event).await.unwrap();
+    assert_eq!(
+        report.success.into_iter().collect::<Vec<_>>(),
+        vec![write_relay.url.clone()]
+    );
+
+    let from_read_relay = client
+        .get::<NewEvent>(|f| vec![f.id(report.event_id)], TIMEOUT)
+        .await
+        .unwrap();
+    assert!(from_read_relay.is_empty());
+
+    let write_relay_client =
+        Client::new_initialized_client_query_only(vec![(write_relay.url.clone(), None)])
+            .await
+            .unwrap();
+    let from_write_relay = write_relay_client
+        .get::<NewEvent>(|f| vec![f.id(report.event_id)], TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(from_write_relay.len(), 1);
+}
+
+#[tokio::test]
+async fn publish_and_get_relay_list() {
+    let relay = MockRelay::run().await;
+    let keys = Keys::generate();
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], keys.clone())
+            .await
+            .unwrap();
+
+    assert!(client
+        .get_relay_list(keys.public_key, TIMEOUT)
+        .await
+        .unwrap()
+        .is_none());
+
+    let relay_list = vec![
+        (relay.url.clone(), None),
+        (
+            "wss://read.example".parse().unwrap(),
+            Some(RelayMetadata::Read),
+        ),
+        (
+            "wss://write.example".parse().unwrap(),
+            Some(RelayMetadata::Write),
+        ),
+    ];
+    client.publish_relay_list(relay_list.clone()).await.unwrap();
+
+    let fetched_relay_list = client
+        .get_relay_list(keys.public_key, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(fetched_relay_list, Some(relay_list));
+}
+
+#[tokio::test]
+async fn publish_without_any_accepting_relay_fails() {
+    let relay = MockRelay::run_rejecting_kinds(vec![Kind::from(NewEvent::KIND_U16)]).await;
+
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], Keys::generate())
+            .await
+            .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let error = client.publish::<NewEvent>(&event).await.err().unwrap();
+    let ClientError::NotPublished(report) = error else {
+        panic!("unexpected error {error}");
+    };
+    assert!(report.success.is_empty());
+    assert_eq!(
+        report.failed.get(&relay.url),
+        Some(&Some("blocked: kind not allowed".to_owned()))
+    );
+}
+
+#[tokio::test]
+async fn publish_reports_the_reason_of_each_refusing_relay() {
+    let accepting_relay = MockRelay::run().await;
+    let refusing_relay = MockRelay::run_rejecting_kinds(vec![Kind::from(NewEvent::KIND_U16)]).await;
+
+    let client = Client::new_initialized_client_signer(
+        vec![
+            (accepting_relay.url.clone(), None),
+            (refusing_relay.url.clone(), None),
+        ],
+        Keys::generate(),
+    )
+    .await
+    .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let report = client.publish::<NewEvent>(&event).await.unwrap();
+    assert_eq!(
+        report.success.into_iter().collect::<Vec<_>>(),
+        vec![accepting_relay.url.clone()]
+    );
+    assert_eq!(
+        report.failed.get(&refusing_relay.url),
+        Some(&Some("blocked: kind not allowed".to_owned()))
+    );
+}
@@ -0,0 +1,90 @@
+use prediction_market_event::{information::Information, Event, EventPayout};
+use prediction_market_event_nostr_client::{ClientError, Position, Settlement};
+
+fn position(holder: &str, outcome: u16, units: u64) -> Position {
+    Position {
+        holder: holder.to_owned(),
+        outcome,
+        units,
+    }
+}
+
+#[test]
+fn holder_amounts_and_remainders() {
+    let event = Event::new_with_random_nonce(3, 3, Information::None);
+    let event_payout = EventPayout::new(&event, vec![2, 1, 0]).unwrap();
+    let positions = [
+        position("alice", 0, 10),
+        position("bob", 1, 10),
+        position("alice", 2, 10),
+        position("carol", 0, 1),
+        position("carol", 1, 1),
+    ];
+
+    let settlement = Settlement::new(&event, &event_payout, &positions).unwrap();
+
+    let holder_payouts: Vec<_> = settlement
+        .holder_payouts
+        .iter()
+        .map(|holder_payout| {
+            (
+                holder_payout.holder.as_str(),
+                holder_payout.amount,
+                holder_payout.remainder,
+            )
+        })
+        .collect();
+    // alice 20/3, bob 10/3, carol 3/3
+    assert_eq!(
+        holder_payouts,
+        vec![("alice", 6, 2), ("bob", 3, 1), ("carol", 1, 0)]
+    );
+    assert_eq!(settlement.total_amount().unwrap(), 10);
+    assert_eq!(settlement.total_remainder().unwrap(), 3);
+}
+
+#[test]
+fn position_in_unknown_outcome_is_rejected() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_payout = EventPayout::new(&event, vec![100, 0]).unwrap();
+
+    assert!(Settlement::new(&event, &event_payout, &[position("alice", 2, 1)]).is_err());
+}
+
+#[test]
+fn event_without_units_to_payout_is_rejected() {
+    let event = Event::new_with_random_nonce(2, 0, Information::None);
+    let event_payout = EventPayout {
+        event_hash_hex: event.hash_hex().unwrap(),
+        units_per_outcome: vec![0, 0],
+    };
+
+    assert!(matches!(
+        Settlement::new(&event, &event_payout, &[position("alice", 0, 1)]),
+        Err(ClientError::Validation(_))
+    ));
+}
+
+#[test]
+fn payout_of_other_event_is_rejected() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let event_payout = EventPayout::new(&other_event, vec![100, 0]).unwrap();
+
+    assert!(Settlement::new(&event, &event_payout, &[position("alice", 0, 1)]).is_err());
+}
+
+#[test]
+fn total_amount_overflow_is_rejected() {
+    let event = Event::new_with_random_nonce(2, 1, Information::None);
+    let event_payout = EventPayout::new(&event, vec![1, 0]).unwrap();
+    let positions = [position("alice", 0, u64::MAX), position("bob", 0, 1)];
+
+    let settlement = Settlement::new(&event, &event_payout, &positions).unwrap();
+
+    assert!(matches!(
+        settlement.total_amount(),
+        Err(ClientError::Validation(_))
+    ));
+    assert_eq!(settlement.total_remainder().unwrap(), 0);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{cli_context, run_cli, MockRelay};
+use nostr_sdk::{EventBuilder, Keys, Kind};
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{NewEvent, NostrEventUtils},
+    Event,
+};
+use prediction_market_event_nostr_client::{cli::exit_code, Client, ClientError};
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+/// Relay with one valid new event and one new event whose content is not a prediction market event.
+async fn relay_with_malformed_new_event() -> MockRelay {
+    let relay = MockRelay::run().await;
+    let client =
+        Client::new_initialized_client_signer(vec![(relay.url.clone(), None)], Keys::generate())
+            .await
+            .unwrap();
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    client.publish::<NewEvent>(&event).await.unwrap();
+    let malformed = EventBuilder::new(Kind::from(NewEvent::KIND_U16), "not an event", [])
+        .to_event(&Keys::generate())
+        .unwrap();
+    client.broadcast(malformed).await.unwrap();
+
+    relay
+}
+
+#[tokio::test]
+async fn strict_get_fails_with_interpretation_error() {
+    let relay = relay_with_malformed_new_event().await;
+    let client = Client::new_initialized_client_query_only(vec![(relay.url.clone(), None)])
+        .await
+        .unwrap();
+
+    let output = client
+        .get_detailed::<NewEvent>(|f| vec![f], TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(output.accepted.len(), 1);
+    assert_eq!(output.rejected.len(), 1);
+
+    let strict = client
+        .get_detailed::<NewEvent>(|f| vec![f], TIMEOUT)
+        .await
+        .unwrap()
+        .strict();
+    assert!(matches!(strict, Err(ClientError::Interpretation(_))));
+}
+
+#[tokio::test]
+async fn strict_query_exits_with_interpretation_code() {
+    let relay = relay_with_malformed_new_event().await;
+    let context = cli_context().await;
+    run_cli(&context, &["relay", "add", relay.url.as_str()])
+        .await
+        .unwrap();
+
+    let lenient = run_cli(&context, &["query", "custom", "new-event"])
+        .await
+        .unwrap();
+    assert_eq!(lenient.as_array().unwrap().len(), 1);
+
+    let e = run_cli(&context, &["query", "custom", "--strict", "new-event"])
+        .await
+        .unwrap_err();
+    assert_eq!(exit_code(&e), 5);
+    // The cause is only printed once, as the source of the error.
+    assert_eq!(e.to_string(), "interpretation failed");
+    let message = format!("{e:#}");
+    assert!(message.starts_with("interpretation failed: "), "{message}");
+    assert_eq!(message.matches("interpretation failed").count(), 1);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockRelay;
+use nostr_sdk::Keys;
+use prediction_market_event::{information::Information, nostr_event_types::NewEvent, Event};
+use prediction_market_event_nostr_client::Client;
+use tokio_stream::StreamExt;
+
+const WAIT: Duration = Duration::from_millis(500);
+
+#[tokio::test]
+async fn event_from_two_relays_is_delivered_once_and_drop_unsubscribes() {
+    let relays = [MockRelay::run().await, MockRelay::run().await];
+    let relay_list: Vec<_> = relays
+        .iter()
+        .map(|relay| (relay.url.clone(), None))
+        .collect();
+    let subscriber = Client::new_initialized_client_query_only(relay_list.clone())
+        .await
+        .unwrap();
+    let publisher = Client::new_initialized_client_signer(relay_list, Keys::generate())
+        .await
+        .unwrap();
+
+    let mut subscription = subscriber.subscribe::<NewEvent>(|f| vec![f]).await.unwrap();
+    for relay in &relays {
+        wait_until(|| relay.open_subscriptions() == 1).await;
+    }
+
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let report = publisher.publish::<NewEvent>(&event).await.unwrap();
+    assert_eq!(report.success.len(), 2);
+
+    let (nostr_event, received_event) = tokio::time::timeout(WAIT, subscription.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(nostr_event.id, report.event_id);
+    assert_eq!(received_event, event);
+    assert!(tokio::time::timeout(WAIT, subscription.next())
+        .await
+        .is_err());
+
+    drop(subscription);
+    for relay in &relays {
+        wait_until(|| relay.open_subscriptions() == 0).await;
+    }
+}
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,129 @@
+use nostr_sdk::{EventBuilder, Keys, Kind, Tag};
+use prediction_market_event::{
+    information::Information,
+    nostr_event_types::{NewEvent, NostrEventUtils},
+    Event,
+};
+use prediction_market_event_nostr_client::{EventVerification, Interpretation};
+
+fn new_event_nostr_event(event: &Event) -> nostr_sdk::Event {
+    NewEvent::create_nostr_event_builder(event)
+        .unwrap()
+        .to_event(&Keys::generate())
+        .unwrap()
+}
+
+/// Changes fields of the signed nostr event without signing it again.
+fn tamper(
+    nostr_event: &nostr_sdk::Event,
+    f: impl FnOnce(&mut nostr_sdk::Event),
+) -> nostr_sdk::Event {
+    let mut nostr_event = nostr_event.clone();
+    f(&mut nostr_event);
+
+    nostr_event
+}
+
+#[test]
+fn valid_new_event() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let verification = EventVerification::new(&new_event_nostr_event(&event));
+
+    assert!(verification.id_valid);
+    assert!(verification.signature_valid);
+    assert!(matches!(
+        verification.interpretation,
+        Some(Ok(Interpretation::NewEvent(ref interpreted))) if *interpreted == event
+    ));
+    let event_hash = verification.event_hash.as_ref().unwrap();
+    assert_eq!(event_hash.hashtag, Some(event.hash_hex().unwrap().0));
+    assert!(event_hash.matches());
+    assert!(verification.is_valid());
+}
+
+#[test]
+fn tampered_content_invalidates_the_id() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let nostr_event = tamper(&new_event_nostr_event(&event), |nostr_event| {
+        nostr_event.content = other_event.try_to_json_string().unwrap();
+    });
+
+    let verification = EventVerification::new(&nostr_event);
+
+    assert!(!verification.id_valid);
+    assert!(!verification.is_valid());
+}
+
+#[test]
+fn tampered_id_invalidates_the_id_and_signature() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_nostr_event = new_event_nostr_event(&event);
+    let nostr_event = tamper(&new_event_nostr_event(&event), |nostr_event| {
+        nostr_event.id = other_nostr_event.id;
+    });
+
+    let verification = EventVerification::new(&nostr_event);
+
+    assert!(!verification.id_valid);
+    assert!(!verification.signature_valid);
+    assert!(!verification.is_valid());
+}
+
+#[test]
+fn bad_signature() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_nostr_event = new_event_nostr_event(&event);
+    let nostr_event = tamper(&new_event_nostr_event(&event), |nostr_event| {
+        nostr_event.sig = other_nostr_event.sig;
+    });
+
+    let verification = EventVerification::new(&nostr_event);
+
+    assert!(verification.id_valid);
+    assert!(!verification.signature_valid);
+    assert!(!verification.is_valid());
+}
+
+#[test]
+fn hashtag_not_matching_the_event_hash() {
+    let event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_event = Event::new_with_random_nonce(2, 100, Information::None);
+    let other_hash_hex = other_event.hash_hex().unwrap();
+    let nostr_event = EventBuilder::new(
+        Kind::from(NewEvent::KIND_U16),
+        event.try_to_json_string().unwrap(),
+        [Tag::hashtag(other_hash_hex.0.clone())],
+    )
+    .to_event(&Keys::generate())
+    .unwrap();
+
+    let verification = EventVerification::new(&nostr_event);
+
+    assert!(verification.id_valid);
+    assert!(verification.signature_valid);
+    assert!(matches!(verification.interpretation, Some(Err(_))));
+    let event_hash = verification.event_hash.as_ref().unwrap();
+    assert_eq!(event_hash.hashtag, Some(other_hash_hex.0));
+    assert_eq!(
+        event_hash.computed.as_ref().unwrap().0,
+        event.hash_hex().unwrap().0
+    );
+    assert!(!event_hash.matches());
+    assert!(!verification.is_valid());
+}
+
+#[test]
+fn other_kinds_are_not_interpreted() {
+    let nostr_event = EventBuilder::text_note("hello", [])
+        .to_event(&Keys::generate())
+        .unwrap();
+
+    let verification = EventVerification::new(&nostr_event);
+
+    assert!(verification.id_valid);
+    assert!(verification.signature_valid);
+    assert!(verification.interpretation.is_none());
+    assert!(verification.event_hash.is_none());
+    assert!(!verification.is_valid());
+}
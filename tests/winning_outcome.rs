@@ -0,0 +1,111 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{cli_context, run_cli};
+use nostr_sdk::{JsonUtil, Keys};
+use prediction_market_event::{
+    information::{Information, V1},
+    nostr_event_types::{EventPayoutAttestation, NewEvent, NostrEventUtils},
+    Event, PayoutUnit,
+};
+use prediction_market_event_nostr_client::cli::{db::NostrEventCache, Context};
+
+fn v1_event(outcome_count: u16, outcome_titles: &[&str]) -> Event {
+    Event::new_with_random_nonce(
+        outcome_count,
+        100,
+        Information::V1(V1 {
+            title: "title".to_owned(),
+            description: String::new(),
+            outcome_titles: outcome_titles.iter().map(|s| (*s).to_owned()).collect(),
+            expected_payout_unix_seconds: 1_700_000_000,
+        }),
+    )
+}
+
+/// Context with a key and the event in its event cache, `--dry-run` only looks up events there.
+async fn context_with_cached_event(event: &Event) -> Context {
+    let context = cli_context().await;
+    run_cli(&context, &["key", "generate"]).await.unwrap();
+    let nostr_event = NewEvent::create_nostr_event_builder(event)
+        .unwrap()
+        .to_event(&Keys::generate())
+        .unwrap();
+    NostrEventCache::put_events(&context, &[nostr_event])
+        .await
+        .unwrap();
+
+    context
+}
+
+async fn attest_winning_outcome(
+    event: &Event,
+    winning_outcome: &str,
+) -> anyhow::Result<Vec<PayoutUnit>> {
+    let context = context_with_cached_event(event).await;
+    let event_hash_hex = event.hash_hex().unwrap();
+    let json = run_cli(
+        &context,
+        &[
+            "publish",
+            "--dry-run",
+            "event-payout-attestation",
+            &event_hash_hex.0,
+            "--winning-outcome",
+            winning_outcome,
+        ],
+    )
+    .await?;
+    let nostr_event = nostr_sdk::Event::from_json(json.to_string()).unwrap();
+    let (_, event_payout) = EventPayoutAttestation::interpret_nostr_event(&nostr_event).unwrap();
+
+    Ok(event_payout.units_per_outcome)
+}
+
+#[tokio::test]
+async fn winning_outcome_by_index() {
+    let event = Event::new_with_random_nonce(3, 100, Information::None);
+
+    assert_eq!(
+        attest_winning_outcome(&event, "1").await.unwrap(),
+        vec![0, 100, 0]
+    );
+    assert!(attest_winning_outcome(&event, "3").await.is_err());
+    assert!(attest_winning_outcome(&event, "yes").await.is_err());
+}
+
+#[tokio::test]
+async fn winning_outcome_by_title() {
+    let event = v1_event(2, &["yes", "no"]);
+
+    assert_eq!(
+        attest_winning_outcome(&event, "no").await.unwrap(),
+        vec![0, 100]
+    );
+    assert!(attest_winning_outcome(&event, "maybe").await.is_err());
+}
+
+#[tokio::test]
+async fn index_wins_over_numeric_title() {
+    let event = v1_event(2, &["1", "5"]);
+
+    // "1" is the index of the second outcome and the title of the first.
+    assert_eq!(
+        attest_winning_outcome(&event, "1").await.unwrap(),
+        vec![0, 100]
+    );
+    // "5" is out of range as an index, so it matches the title.
+    assert_eq!(
+        attest_winning_outcome(&event, "5").await.unwrap(),
+        vec![0, 100]
+    );
+}
+
+#[tokio::test]
+async fn title_of_outcome_out_of_range_is_rejected() {
+    let event = v1_event(2, &["yes", "no", "maybe"]);
+
+    let e = attest_winning_outcome(&event, "maybe").await.unwrap_err();
+    assert!(e.to_string().contains("only has 2 outcomes"), "{e}");
+}